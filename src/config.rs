@@ -1,11 +1,12 @@
 use std::{collections::HashMap, net::Ipv4Addr};
 
 use camino::{Utf8Path, Utf8PathBuf};
+use chrono::Duration;
 use config::{Config, ConfigError};
 use mac_address::MacAddress;
 use serde::{Deserialize, Serialize};
 
-use crate::hue::api::RoomArchetype;
+use crate::hue::api::{ColorGamut, DeviceArchetype, GamutType, RoomArchetype};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BridgeConfig {
@@ -14,9 +15,66 @@ pub struct BridgeConfig {
     pub ipaddress: Ipv4Addr,
     pub http_port: u16,
     pub https_port: u16,
+    pub entertainment_port: u16,
+    /// Number of seconds zigbee2mqtt permits joining, when searching for
+    /// new devices
+    pub search_duration: u64,
     pub netmask: Ipv4Addr,
     pub gateway: Ipv4Addr,
     pub timezone: String,
+    #[serde(default)]
+    pub linkbutton: LinkButtonConfig,
+    /// Location of the bridge, used for sunrise/sunset calculations
+    pub location: Option<LocationConfig>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct LocationConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkButtonConfig {
+    /// Number of seconds the link button stays "pressed"
+    #[serde(default = "LinkButtonConfig::default_duration")]
+    pub duration: u64,
+    /// Friendly name of z2m device that can press the link button
+    pub z2m_device: Option<String>,
+    /// Action value (from `z2m_device`) that presses the link button
+    ///
+    /// If not set, any action from the device will do.
+    pub z2m_action: Option<String>,
+    /// Allow requests from the bifrost host itself to press the link button,
+    /// without an application key
+    #[serde(default)]
+    pub allow_local: bool,
+}
+
+impl LinkButtonConfig {
+    const fn default_duration() -> u64 {
+        30
+    }
+
+    /// Duration of link button press, using the configured default if
+    /// `seconds` is not specified
+    #[must_use]
+    pub fn press_duration(&self, seconds: Option<u64>) -> Duration {
+        /* cap the duration at one day, to avoid overflow on silly values */
+        let seconds = seconds.unwrap_or(self.duration).min(24 * 60 * 60);
+        Duration::seconds(i64::try_from(seconds).unwrap_or_default())
+    }
+}
+
+impl Default for LinkButtonConfig {
+    fn default() -> Self {
+        Self {
+            duration: Self::default_duration(),
+            z2m_device: None,
+            z2m_action: None,
+            allow_local: false,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -35,12 +93,72 @@ pub struct Z2mConfig {
 pub struct Z2mServer {
     pub url: String,
     pub group_prefix: Option<String>,
+    /// Also remove (unpair) devices from zigbee2mqtt, when they are deleted
+    /// from the Hue app
+    #[serde(default)]
+    pub remove_devices: bool,
+
+    /* mqtt transport only */
+    pub base_topic: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub ca_file: Option<Utf8PathBuf>,
+}
+
+impl Z2mServer {
+    const DEFAULT_BASE_TOPIC: &'static str = "zigbee2mqtt";
+
+    /// True if this server should be reached through an mqtt broker,
+    /// rather than the zigbee2mqtt websocket frontend
+    #[must_use]
+    pub fn is_mqtt(&self) -> bool {
+        self.url.starts_with("mqtt://") || self.url.starts_with("mqtts://")
+    }
+
+    #[must_use]
+    pub fn base_topic(&self) -> &str {
+        self.base_topic
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_BASE_TOPIC)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct RoomConfig {
     pub name: Option<String>,
     pub icon: Option<RoomArchetype>,
+    #[serde(default)]
+    pub zone: bool,
+}
+
+/// Color gamut of a light, either one of the standard gamut types ("A",
+/// "B", "C"), or the red, green and blue corners
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GamutConfig {
+    Type(GamutType),
+    Custom(ColorGamut),
+}
+
+impl GamutConfig {
+    #[must_use]
+    pub fn resolve(&self) -> Option<(GamutType, ColorGamut)> {
+        match self {
+            Self::Type(gamut_type) => Some((*gamut_type, gamut_type.gamut()?)),
+            Self::Custom(gamut) => Some((GamutType::Other, gamut.clone())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct DeviceConfig {
+    /// Archetype (icon) to present this device with, instead of the guessed one
+    pub archetype: Option<DeviceArchetype>,
+    /// Color gamut of this light, instead of the guessed one
+    pub gamut: Option<GamutConfig>,
+    /// Emulate color temperature on lights that only support colors
+    #[serde(default)]
+    pub emulate_ct: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -50,6 +168,19 @@ pub struct AppConfig {
     pub bifrost: BifrostConfig,
     #[serde(default)]
     pub rooms: HashMap<String, RoomConfig>,
+    #[serde(default)]
+    pub devices: HashMap<String, DeviceConfig>,
+}
+
+impl AppConfig {
+    /// Configuration of a device, by friendly name or ieee address
+    #[must_use]
+    pub fn device(&self, friendly_name: &str, ieee_address: &str) -> Option<&DeviceConfig> {
+        self.devices
+            .iter()
+            .find(|(key, _)| *key == friendly_name || key.eq_ignore_ascii_case(ieee_address))
+            .map(|(_, conf)| conf)
+    }
 }
 
 pub fn parse(filename: &Utf8Path) -> Result<AppConfig, ConfigError> {
@@ -58,6 +189,8 @@ pub fn parse(filename: &Utf8Path) -> Result<AppConfig, ConfigError> {
         .set_default("bifrost.cert_file", "cert.pem")?
         .set_default("bridge.http_port", 80)?
         .set_default("bridge.https_port", 443)?
+        .set_default("bridge.entertainment_port", 2100)?
+        .set_default("bridge.search_duration", 60)?
         .add_source(config::File::with_name(filename.as_str()))
         .build()?;
 
@@ -63,6 +63,9 @@ pub enum ApiError {
     #[error(transparent)]
     TungsteniteError(#[from] tokio_tungstenite::tungstenite::Error),
 
+    #[error(transparent)]
+    MqttClientError(#[from] rumqttc::ClientError),
+
     #[error(transparent)]
     X509DerError(#[from] x509_cert::der::Error),
 
@@ -78,6 +81,12 @@ pub enum ApiError {
     #[error(transparent)]
     P256Pkcs8Error(#[from] p256::pkcs8::Error),
 
+    #[error(transparent)]
+    OpenSslError(#[from] openssl::error::ErrorStack),
+
+    #[error(transparent)]
+    SslError(#[from] openssl::ssl::Error),
+
     /* zigbee2mqtt errors */
     #[error("Unexpected eof on z2m socket")]
     UnexpectedZ2mEof,
@@ -85,6 +94,9 @@ pub enum ApiError {
     #[error("Unexpected z2m message: {0:?}")]
     UnexpectedZ2mReply(tokio_tungstenite::tungstenite::Message),
 
+    #[error("Invalid z2m server url: {0}")]
+    InvalidZ2mUrl(String),
+
     /* hue api v1 errors */
     #[error("Cannot create resources of type: {0:?}")]
     V1CreateUnsupported(ApiResourceType),
@@ -92,7 +104,19 @@ pub enum ApiError {
     #[error("Resource {0} not found")]
     V1NotFound(u32),
 
+    #[error("Invalid time pattern: {0:?}")]
+    V1InvalidTimePattern(String),
+
+    #[error("Invalid v1 request: {0}")]
+    V1InvalidRequest(&'static str),
+
+    #[error("Unsupported v1 command: {0} {1}")]
+    V1CommandUnsupported(String, String),
+
     /* hue api v2 errors */
+    #[error("unauthorized user")]
+    Unauthorized,
+
     #[error("State changes not supported for: {0:?}")]
     UpdateUnsupported(RType),
 
@@ -120,6 +144,16 @@ pub enum ApiError {
 
     #[error("Cannot parse certificate: {0:?}")]
     CertificateInvalid(Utf8PathBuf),
+
+    #[error("Link button request failed: {0}")]
+    LinkButtonFailed(String),
+
+    /* entertainment errors */
+    #[error("Invalid HueStream packet: {0}")]
+    HueStreamInvalid(&'static str),
+
+    #[error("Unknown entertainment client: {0:?}")]
+    EntertainmentUnknownClient(String),
 }
 
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::hue::api::{DollarRef, ResourceLink, SmartSceneTime, Weekday};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorScriptMetadata {
+    pub name: String,
+    pub category: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorScript {
+    pub configuration_schema: DollarRef,
+    pub description: String,
+    pub max_number_instances: Option<u32>,
+    pub metadata: BehaviorScriptMetadata,
+    pub state_schema: DollarRef,
+    pub supported_features: Vec<String>,
+    pub trigger_schema: DollarRef,
+    pub version: String,
+}
+
+/// The behavior scripts executed by bifrost, with the ids used by a real
+/// bridge (which is how apps recognize them)
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum BehaviorScriptType {
+    WakeUp,
+    GoToSleep,
+    Timer,
+    ComingHome,
+}
+
+impl BehaviorScriptType {
+    pub const ALL: [Self; 4] = [Self::WakeUp, Self::GoToSleep, Self::Timer, Self::ComingHome];
+
+    #[must_use]
+    pub const fn id(self) -> Uuid {
+        match self {
+            Self::WakeUp => Uuid::from_u128(0xff89_57e3_2eb9_4699_a0c8_ad2c_b3ed_e704),
+            Self::GoToSleep => Uuid::from_u128(0x7238_c707_8693_4f19_9095_ccdc_1444_d228),
+            Self::Timer => Uuid::from_u128(0xe73b_c72d_96b1_46f8_aa57_7298_61f8_0c78),
+            Self::ComingHome => Uuid::from_u128(0xfd60_fcd1_4809_4813_b510_4a18_856a_595c),
+        }
+    }
+
+    #[must_use]
+    pub fn from_id(id: &Uuid) -> Option<Self> {
+        Self::ALL.into_iter().find(|st| st.id() == *id)
+    }
+
+    #[must_use]
+    pub fn script(self) -> BehaviorScript {
+        let (name, category, schema, description, features): (_, _, _, _, &[&str]) = match self {
+            Self::WakeUp => (
+                "Basic wake up routine",
+                "automation",
+                "basic_wake_up",
+                "Get your body in the mood to wake up by fading on the lights in the morning.",
+                &["style_sunrise", "intensity"],
+            ),
+            Self::GoToSleep => (
+                "Basic go to sleep routine",
+                "automation",
+                "basic_goto_sleep",
+                "Get ready for nice sleep by fading the lights off in the evening.",
+                &[],
+            ),
+            Self::Timer => ("Timers", "automation", "timer", "Countdown Timer", &[]),
+            Self::ComingHome => (
+                "Coming home",
+                "automation",
+                "coming_home",
+                "Automatically turn your lights on when you come home.",
+                &[],
+            ),
+        };
+
+        BehaviorScript {
+            configuration_schema: DollarRef {
+                dref: format!("{schema}_config.json#"),
+            },
+            description: description.to_string(),
+            max_number_instances: None,
+            metadata: BehaviorScriptMetadata {
+                name: name.to_string(),
+                category: category.to_string(),
+            },
+            state_schema: DollarRef {
+                dref: format!("{schema}_state.json#"),
+            },
+            supported_features: features.iter().map(ToString::to_string).collect(),
+            trigger_schema: DollarRef {
+                dref: String::from("trigger.json#"),
+            },
+            version: String::from("0.0.1"),
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorInstanceStatus {
+    Initializing,
+    Running,
+    Disabled,
+    Errored,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorInstanceMetadata {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorInstance {
+    pub script_id: Uuid,
+    pub enabled: bool,
+    /// Script-specific runtime state, maintained by bifrost
+    #[serde(default)]
+    pub state: Value,
+    /// Script-specific configuration, as supplied by the app
+    pub configuration: Value,
+    #[serde(default)]
+    pub dependees: Vec<Value>,
+    pub status: BehaviorInstanceStatus,
+    #[serde(default)]
+    pub last_error: String,
+    pub metadata: BehaviorInstanceMetadata,
+}
+
+impl BehaviorInstance {
+    #[must_use]
+    pub fn script_type(&self) -> Option<BehaviorScriptType> {
+        BehaviorScriptType::from_id(&self.script_id)
+    }
+
+    #[must_use]
+    pub const fn status_for(enabled: bool) -> BehaviorInstanceStatus {
+        if enabled {
+            BehaviorInstanceStatus::Running
+        } else {
+            BehaviorInstanceStatus::Disabled
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorInstanceNew {
+    pub script_id: Uuid,
+    #[serde(default)]
+    pub enabled: bool,
+    pub configuration: Value,
+    pub metadata: BehaviorInstanceMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BehaviorInstanceUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configuration: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BehaviorInstanceMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<BehaviorInstanceStatus>,
+}
+
+/* Script configurations, as far as bifrost needs to understand them */
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorDuration {
+    pub seconds: u32,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorTimePoint {
+    pub time: SmartSceneTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorWhen {
+    /// Days to repeat on. If not set, the routine runs only once.
+    pub recurrence_days: Option<Vec<Weekday>>,
+    pub time_point: BehaviorTimePoint,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorWhere {
+    pub group: ResourceLink,
+    /// Specific lights in the group, if not the whole group
+    pub items: Option<Vec<ResourceLink>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WakeUpConfig {
+    #[serde(default = "WakeUpConfig::default_end_brightness")]
+    pub end_brightness: f64,
+    pub fade_in_duration: BehaviorDuration,
+    pub turn_lights_off_after: Option<BehaviorDuration>,
+    pub style: Option<String>,
+    pub when: BehaviorWhen,
+    #[serde(rename = "where")]
+    pub where_: Vec<BehaviorWhere>,
+}
+
+impl WakeUpConfig {
+    const fn default_end_brightness() -> f64 {
+        100.0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoToSleepConfig {
+    pub end_state: Option<String>,
+    pub fade_out_duration: BehaviorDuration,
+    pub when: BehaviorWhen,
+    #[serde(rename = "where")]
+    pub where_: Vec<BehaviorWhere>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimerConfig {
+    pub duration: BehaviorDuration,
+    #[serde(rename = "where")]
+    pub where_: Vec<BehaviorWhere>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComingHomeWhat {
+    pub group: ResourceLink,
+    /// Scene to recall. If not set, the lights are turned on.
+    pub recall: Option<ResourceLink>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComingHomeConfig {
+    pub what: Vec<ComingHomeWhat>,
+}
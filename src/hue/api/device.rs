@@ -17,7 +17,45 @@ impl Device {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<DeviceMetadataUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<ResourceLink>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identify: Option<DeviceIdentify>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceMetadataUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archetype: Option<DeviceArchetype>,
+}
+
+impl From<&Metadata> for DeviceMetadataUpdate {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            name: Some(metadata.name.clone()),
+            archetype: Some(metadata.archetype.clone()),
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceIdentifyAction {
+    Identify,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceIdentify {
+    pub action: DeviceIdentifyAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct DeviceProductData {
     pub model_id: String,
     pub manufacturer_name: String,
@@ -54,7 +92,7 @@ impl DeviceProductData {
         let certified = manufacturer_name == Self::SIGNIFY_MANUFACTURER_NAME;
         let software_version = str_or_unknown(&dev.software_build_id);
 
-        let product_archetype = DeviceArchetype::SpotBulb;
+        let product_archetype = DeviceArchetype::guess_from_device(dev);
 
         Self {
             model_id,
@@ -67,7 +105,7 @@ impl DeviceProductData {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum DeviceArchetype {
     BridgeV2,
@@ -124,3 +162,91 @@ pub enum DeviceArchetype {
     #[serde(untagged)]
     Other(String),
 }
+
+impl DeviceArchetype {
+    /// Keywords found in the z2m definition (vendor, model, description) of
+    /// a device, and the archetype they suggest. Keywords match whole words
+    /// (or their plural), and the first match wins, so more specific
+    /// keywords come first.
+    const KEYWORDS: &'static [(&'static str, Self)] = &[
+        ("plug", Self::Plug),
+        ("socket", Self::Plug),
+        ("outlet", Self::Plug),
+        ("lightstrip", Self::HueLightstrip),
+        ("light strip", Self::HueLightstrip),
+        ("led strip", Self::HueLightstrip),
+        ("hue go", Self::HueGo),
+        ("hue play", Self::HuePlay),
+        ("bloom", Self::HueBloom),
+        ("iris", Self::HueIris),
+        ("signe", Self::HueSigne),
+        ("centris", Self::HueCentris),
+        ("tube", Self::HueTube),
+        ("bollard", Self::Bollard),
+        ("christmas", Self::ChristmasTree),
+        ("string", Self::StringLight),
+        ("pendant", Self::PendantRound),
+        ("ceiling", Self::CeilingRound),
+        ("downlight", Self::RecessedCeiling),
+        ("recessed", Self::RecessedCeiling),
+        ("floor", Self::FloorShade),
+        ("table", Self::TableShade),
+        ("wall", Self::WallShade),
+        ("candle", Self::CandleBulb),
+        ("e12", Self::CandleBulb),
+        ("e14", Self::CandleBulb),
+        ("filament", Self::EdisonBulb),
+        ("edison", Self::EdisonBulb),
+        ("vintage", Self::VintageBulb),
+        ("globe", Self::LargeGlobeBulb),
+        ("gu10", Self::SpotBulb),
+        ("mr16", Self::SpotBulb),
+        ("spot", Self::SpotBulb),
+        ("br30", Self::FloodBulb),
+        ("par38", Self::FloodBulb),
+        ("flood", Self::FloodBulb),
+    ];
+
+    /// Best guess of the archetype of a z2m device, from the kind of device
+    /// it is and (for lights) its definition
+    #[must_use]
+    pub fn guess_from_device(dev: &z2m::api::Device) -> Self {
+        if dev.expose_light().is_none() {
+            return if dev.expose_plug().is_some() {
+                Self::Plug
+            } else {
+                Self::UnknownArchetype
+            };
+        }
+
+        let text = dev
+            .definition
+            .as_ref()
+            .map(|def| format!("{} {} {}", def.vendor, def.model, def.description))
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        Self::KEYWORDS
+            .iter()
+            .find(|(keyword, _)| Self::contains_words(&words, keyword))
+            .map_or(Self::ClassicBulb, |(_, archetype)| archetype.clone())
+    }
+
+    /// True if the (space separated) words of `keyword` appear in `words`,
+    /// in order
+    fn contains_words(words: &[&str], keyword: &str) -> bool {
+        let keyword: Vec<&str> = keyword.split(' ').collect();
+
+        words.windows(keyword.len()).any(|window| {
+            window
+                .iter()
+                .zip(&keyword)
+                .all(|(word, kw)| word == kw || word.strip_suffix('s') == Some(kw))
+        })
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::hue::api::{Entertainment, RType, ResourceLink};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfiguration {
+    pub metadata: EntertainmentConfigurationMetadata,
+    pub name: String,
+    pub configuration_type: EntertainmentConfigurationType,
+    pub status: EntertainmentConfigurationStatus,
+    pub stream_proxy: EntertainmentConfigurationStreamProxy,
+    pub channels: Vec<EntertainmentConfigurationChannel>,
+    pub locations: EntertainmentConfigurationLocations,
+    pub light_services: Vec<ResourceLink>,
+}
+
+impl EntertainmentConfiguration {
+    /// Build a configuration from the requested service locations.
+    ///
+    /// Every position of every service becomes a separate channel, in
+    /// order, which is what streaming clients expect to address.
+    #[must_use]
+    pub fn new(
+        name: String,
+        configuration_type: EntertainmentConfigurationType,
+        locations: EntertainmentConfigurationLocations,
+        services: &[(ResourceLink, &Entertainment)],
+    ) -> Self {
+        let mut channels = vec![];
+        for loc in &locations.service_locations {
+            for (index, position) in loc.positions.iter().enumerate() {
+                channels.push(EntertainmentConfigurationChannel {
+                    #[allow(clippy::cast_possible_truncation)]
+                    channel_id: channels.len() as u8,
+                    position: *position,
+                    members: vec![EntertainmentConfigurationChannelMember {
+                        service: loc.service,
+                        #[allow(clippy::cast_possible_truncation)]
+                        index: index as u32,
+                    }],
+                });
+            }
+        }
+
+        let light_services = locations
+            .service_locations
+            .iter()
+            .filter_map(|loc| services.iter().find(|(link, _)| *link == loc.service))
+            .filter_map(|(_, ent)| ent.renderer_reference)
+            .collect();
+
+        let node = locations
+            .service_locations
+            .first()
+            .map_or(RType::Entertainment.link_to(Uuid::nil()), |loc| loc.service);
+
+        Self {
+            metadata: EntertainmentConfigurationMetadata { name: name.clone() },
+            name,
+            configuration_type,
+            status: EntertainmentConfigurationStatus::Inactive,
+            stream_proxy: EntertainmentConfigurationStreamProxy {
+                mode: EntertainmentConfigurationStreamProxyMode::Auto,
+                node,
+            },
+            channels,
+            locations,
+            light_services,
+        }
+    }
+
+    /// The light rendering the given channel, if any
+    #[must_use]
+    pub fn channel_service(&self, channel_id: u8) -> Option<&ResourceLink> {
+        self.channels
+            .iter()
+            .find(|ch| ch.channel_id == channel_id)
+            .and_then(|ch| ch.members.first())
+            .map(|member| &member.service)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationMetadata {
+    pub name: String,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntertainmentConfigurationType {
+    Screen,
+    Monitor,
+    Music,
+    #[serde(rename = "3dspace")]
+    Space3D,
+    Other,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntertainmentConfigurationStatus {
+    Active,
+    Inactive,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntertainmentConfigurationStreamProxyMode {
+    Auto,
+    Manual,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationStreamProxy {
+    pub mode: EntertainmentConfigurationStreamProxyMode,
+    pub node: ResourceLink,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationChannel {
+    pub channel_id: u8,
+    pub position: Position,
+    pub members: Vec<EntertainmentConfigurationChannelMember>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationChannelMember {
+    pub service: ResourceLink,
+    pub index: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EntertainmentConfigurationLocations {
+    pub service_locations: Vec<EntertainmentConfigurationServiceLocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationServiceLocation {
+    pub service: ResourceLink,
+    #[serde(default)]
+    pub positions: Vec<Position>,
+    #[serde(default = "default_equalization_factor")]
+    pub equalization_factor: f64,
+}
+
+const fn default_equalization_factor() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntertainmentConfigurationNew {
+    pub metadata: EntertainmentConfigurationMetadata,
+    pub configuration_type: EntertainmentConfigurationType,
+    #[serde(default)]
+    pub locations: EntertainmentConfigurationLocations,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntertainmentConfigurationAction {
+    Start,
+    Stop,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EntertainmentConfigurationUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<EntertainmentConfigurationMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configuration_type: Option<EntertainmentConfigurationType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<EntertainmentConfigurationAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<EntertainmentConfigurationStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locations: Option<EntertainmentConfigurationLocations>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<Vec<EntertainmentConfigurationChannel>>,
+}
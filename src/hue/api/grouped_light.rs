@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::hue::api::{ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, On, ResourceLink};
+use crate::hue::api::{
+    ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, DynamicsUpdate, LightAlertUpdate,
+    LightSignalingUpdate, On, ResourceLink,
+};
 use crate::model::types::XY;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +44,12 @@ pub struct GroupedLightUpdate {
     pub color: Option<ColorUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color_temperature: Option<ColorTemperatureUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamics: Option<DynamicsUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<LightAlertUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signaling: Option<LightSignalingUpdate>,
 }
 
 impl GroupedLightUpdate {
@@ -49,6 +58,14 @@ impl GroupedLightUpdate {
         Self::default()
     }
 
+    /// The z2m effect to trigger, if any
+    #[must_use]
+    pub fn z2m_effect(&self) -> Option<&'static str> {
+        self.signaling
+            .map(|sig| sig.signal.z2m_effect())
+            .or_else(|| self.alert.map(|alert| alert.action.z2m_effect()))
+    }
+
     #[must_use]
     pub fn with_brightness(self, brightness: Option<f64>) -> Self {
         Self {
@@ -5,6 +5,7 @@ use serde_json::Value;
 
 use crate::hue::api::{Metadata, ResourceLink};
 use crate::model::types::XY;
+use crate::z2m;
 use crate::z2m::api::Expose;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,7 +13,7 @@ pub struct Light {
     pub owner: ResourceLink,
     pub metadata: Metadata,
 
-    pub alert: Option<Vec<Value>>,
+    pub alert: Option<LightAlert>,
     pub color: Option<LightColor>,
     pub color_temperature: Option<ColorTemperature>,
     pub dimming: Option<Dimming>,
@@ -99,6 +100,10 @@ impl Sub<&Light> for &Light {
             dimming: None,
             color: None,
             color_temperature: None,
+            dynamics: None,
+            alert: None,
+            signaling: None,
+            effects: None,
         };
 
         if self.on != rhs.on {
@@ -145,13 +150,81 @@ pub struct LightPowerup {
     pub data: Value,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightAlert {
+    pub action_values: Vec<LightAlertAction>,
+}
+
+impl LightAlert {
+    /// Alerts are supported, if the z2m "effect" expose can breathe
+    #[must_use]
+    pub fn extract_from_expose(expose: &Expose) -> Option<Self> {
+        let Expose::Enum(effect) = expose else {
+            return None;
+        };
+
+        effect
+            .values
+            .iter()
+            .any(|val| val == LightAlertAction::Breathe.z2m_effect())
+            .then(|| Self {
+                action_values: vec![LightAlertAction::Breathe],
+            })
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LightAlertAction {
+    Breathe,
+}
+
+impl LightAlertAction {
+    #[must_use]
+    pub const fn z2m_effect(self) -> &'static str {
+        match self {
+            Self::Breathe => "breathe",
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct LightAlertUpdate {
+    pub action: LightAlertAction,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LightSignaling {
     pub signal_values: Vec<LightSignal>,
     pub status: Value,
 }
 
-#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default)]
+impl LightSignaling {
+    /// Signals supported by the z2m "effect" expose
+    #[must_use]
+    pub fn extract_from_expose(expose: &Expose) -> Option<Self> {
+        let Expose::Enum(effect) = expose else {
+            return None;
+        };
+
+        let signal_values: Vec<LightSignal> = [
+            LightSignal::NoSignal,
+            LightSignal::OnOff,
+            LightSignal::OnOffColor,
+            LightSignal::Alternating,
+        ]
+        .into_iter()
+        .filter(|sig| effect.values.iter().any(|val| val == sig.z2m_effect()))
+        .collect();
+
+        (signal_values.len() > 1).then_some(Self {
+            signal_values,
+            status: Value::Null,
+        })
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum LightSignal {
     #[default]
@@ -161,6 +234,25 @@ pub enum LightSignal {
     Alternating,
 }
 
+impl LightSignal {
+    #[must_use]
+    pub const fn z2m_effect(self) -> &'static str {
+        match self {
+            Self::NoSignal => "stop_effect",
+            Self::OnOff | Self::OnOffColor => "blink",
+            Self::Alternating => "okay",
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct LightSignalingUpdate {
+    pub signal: LightSignal,
+    /// Duration of the signal, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum LightDynamicsStatus {
@@ -176,11 +268,89 @@ pub struct LightDynamics {
     pub speed_valid: bool,
 }
 
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LightEffect {
+    #[default]
+    NoEffect,
+    Candle,
+    Fire,
+    Prism,
+    Sparkle,
+    Opal,
+    Glisten,
+    Underwater,
+    Cosmos,
+    Sunbeam,
+    Enchant,
+}
+
+impl LightEffect {
+    pub const ALL: [Self; 11] = [
+        Self::NoEffect,
+        Self::Candle,
+        Self::Fire,
+        Self::Prism,
+        Self::Sparkle,
+        Self::Opal,
+        Self::Glisten,
+        Self::Underwater,
+        Self::Cosmos,
+        Self::Sunbeam,
+        Self::Enchant,
+    ];
+
+    /// Name of this effect in zigbee2mqtt
+    #[must_use]
+    pub const fn z2m_effect(self) -> &'static str {
+        match self {
+            Self::NoEffect => "stop_effect",
+            Self::Candle => "candle",
+            Self::Fire => "fireplace",
+            Self::Prism => "colorloop",
+            Self::Sparkle => "sparkle",
+            Self::Opal => "opal",
+            Self::Glisten => "glisten",
+            Self::Underwater => "underwater",
+            Self::Cosmos => "cosmos",
+            Self::Sunbeam => "sunbeam",
+            Self::Enchant => "enchant",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LightEffects {
-    pub status_values: Value,
-    pub status: Value,
-    pub effect_values: Value,
+    pub status_values: Vec<LightEffect>,
+    pub status: LightEffect,
+    pub effect_values: Vec<LightEffect>,
+}
+
+impl LightEffects {
+    /// Effects supported by the z2m "effect" expose
+    #[must_use]
+    pub fn extract_from_expose(expose: &Expose) -> Option<Self> {
+        let Expose::Enum(effect) = expose else {
+            return None;
+        };
+
+        let effect_values: Vec<LightEffect> = LightEffect::ALL
+            .into_iter()
+            .filter(|eff| effect.values.iter().any(|val| val == eff.z2m_effect()))
+            .collect();
+
+        /* "stop_effect" alone is not worth advertising */
+        (effect_values.len() > 1).then(|| Self {
+            status_values: effect_values.clone(),
+            status: LightEffect::NoEffect,
+            effect_values,
+        })
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct LightEffectsUpdate {
+    pub effect: LightEffect,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -200,6 +370,14 @@ pub struct LightUpdate {
     pub color: Option<ColorUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color_temperature: Option<ColorTemperatureUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamics: Option<DynamicsUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<LightAlertUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signaling: Option<LightSignalingUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effects: Option<LightEffectsUpdate>,
 }
 
 impl LightUpdate {
@@ -208,6 +386,15 @@ impl LightUpdate {
         Self::default()
     }
 
+    /// The z2m effect to trigger, if any
+    #[must_use]
+    pub fn z2m_effect(&self) -> Option<&'static str> {
+        self.effects
+            .map(|eff| eff.effect.z2m_effect())
+            .or_else(|| self.signaling.map(|sig| sig.signal.z2m_effect()))
+            .or_else(|| self.alert.map(|alert| alert.action.z2m_effect()))
+    }
+
     #[must_use]
     pub fn with_brightness(self, dim: Option<impl Into<f64>>) -> Self {
         Self {
@@ -261,6 +448,21 @@ impl From<Dimming> for DimmingUpdate {
     }
 }
 
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DynamicsUpdate {
+    /// Transition time, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+}
+
+impl DynamicsUpdate {
+    /// Transition time in seconds, as used by zigbee2mqtt
+    #[must_use]
+    pub fn transition(&self) -> Option<f64> {
+        self.duration.map(|ms| f64::from(ms) / 1000.0)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Delta {}
 
@@ -300,7 +502,7 @@ impl ColorTemperatureUpdate {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ColorGamut {
     pub red: XY,
     pub green: XY,
@@ -308,6 +510,21 @@ pub struct ColorGamut {
 }
 
 impl ColorGamut {
+    pub const GAMUT_A: Self = Self {
+        red: XY { x: 0.704, y: 0.296 },
+        green: XY {
+            x: 0.2151,
+            y: 0.7106,
+        },
+        blue: XY { x: 0.138, y: 0.080 },
+    };
+
+    pub const GAMUT_B: Self = Self {
+        red: XY { x: 0.675, y: 0.322 },
+        green: XY { x: 0.409, y: 0.518 },
+        blue: XY { x: 0.167, y: 0.040 },
+    };
+
     pub const GAMUT_C: Self = Self {
         blue: XY {
             x: 0.1532,
@@ -337,9 +554,111 @@ impl ColorGamut {
             y: 0.027_116,
         },
     };
+
+    /// Close to the spectral locus, for RGB(W) led controllers and other
+    /// lights with saturated primaries
+    pub const WIDE: Self = Self {
+        red: XY {
+            x: 0.7006,
+            y: 0.2993,
+        },
+        green: XY {
+            x: 0.1387,
+            y: 0.8148,
+        },
+        blue: XY {
+            x: 0.1510,
+            y: 0.0227,
+        },
+    };
+
+    /// Known gamuts, by z2m vendor and zigbee model id prefix (where an
+    /// empty prefix matches any model). The first match wins.
+    const KNOWN: &'static [(&'static str, &'static str, GamutType, Self)] = &[
+        /* the hue go is the only "LLC" light with gamut C */
+        ("philips", "LLC020", GamutType::C, Self::GAMUT_C),
+        ("philips", "LLC", GamutType::A, Self::GAMUT_A),
+        ("philips", "LST001", GamutType::A, Self::GAMUT_A),
+        ("philips", "LCT001", GamutType::B, Self::GAMUT_B),
+        ("philips", "LCT002", GamutType::B, Self::GAMUT_B),
+        ("philips", "LCT003", GamutType::B, Self::GAMUT_B),
+        ("philips", "LCT007", GamutType::B, Self::GAMUT_B),
+        ("philips", "LLM001", GamutType::B, Self::GAMUT_B),
+        ("philips", "", GamutType::C, Self::GAMUT_C),
+        ("ikea", "", GamutType::Other, Self::IKEA_ESTIMATE),
+        ("innr", "", GamutType::Other, Self::WIDE),
+        ("gledopto", "", GamutType::Other, Self::WIDE),
+    ];
+
+    /// Best guess of the gamut of a z2m device, falling back to gamut C
+    #[must_use]
+    pub fn guess_from_device(dev: &z2m::api::Device) -> (GamutType, Self) {
+        let vendor = dev
+            .definition
+            .as_ref()
+            .map(|def| def.vendor.to_lowercase())
+            .unwrap_or_default();
+        let model = dev.model_id.as_deref().unwrap_or_default();
+
+        Self::guess(&vendor, model)
+    }
+
+    /// Best guess of the gamut for a (lowercase) vendor and model id
+    fn guess(vendor: &str, model: &str) -> (GamutType, Self) {
+        Self::KNOWN
+            .iter()
+            .find(|(ven, prefix, _, _)| vendor.starts_with(ven) && model.starts_with(prefix))
+            .map_or(
+                (GamutType::C, Self::GAMUT_C),
+                |(_, _, gamut_type, gamut)| (*gamut_type, gamut.clone()),
+            )
+    }
+
+    #[must_use]
+    pub fn contains(&self, xy: XY) -> bool {
+        fn cross(o: XY, a: XY, b: XY) -> f64 {
+            (a.x - o.x).mul_add(b.y - o.y, -((a.y - o.y) * (b.x - o.x)))
+        }
+
+        let sides = [
+            cross(self.red, self.green, xy),
+            cross(self.green, self.blue, xy),
+            cross(self.blue, self.red, xy),
+        ];
+
+        sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+    }
+
+    /// The closest color inside the gamut
+    #[must_use]
+    pub fn clamp(&self, xy: XY) -> XY {
+        /* closest point to xy on the line segment from a to b */
+        fn closest(a: XY, b: XY, xy: XY) -> XY {
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let t = ((xy.x - a.x).mul_add(dx, (xy.y - a.y) * dy) / dx.mul_add(dx, dy * dy))
+                .clamp(0.0, 1.0);
+            XY::new(t.mul_add(dx, a.x), t.mul_add(dy, a.y))
+        }
+
+        if self.contains(xy) {
+            return xy;
+        }
+
+        [
+            closest(self.red, self.green, xy),
+            closest(self.green, self.blue, xy),
+            closest(self.blue, self.red, xy),
+        ]
+        .into_iter()
+        .min_by(|a, b| {
+            let dist = |p: &XY| (p.x - xy.x).hypot(p.y - xy.y);
+            dist(a).total_cmp(&dist(b))
+        })
+        .unwrap_or(xy)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum GamutType {
     A,
     B,
@@ -347,6 +666,19 @@ pub enum GamutType {
     Other,
 }
 
+impl GamutType {
+    /// The gamut of the standard (Hue) gamut types
+    #[must_use]
+    pub const fn gamut(self) -> Option<ColorGamut> {
+        match self {
+            Self::A => Some(ColorGamut::GAMUT_A),
+            Self::B => Some(ColorGamut::GAMUT_B),
+            Self::C => Some(ColorGamut::GAMUT_C),
+            Self::Other => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LightColor {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -377,6 +709,15 @@ impl LightColor {
             xy: XY::D65_WHITE_POINT,
         })
     }
+
+    #[must_use]
+    pub const fn with_gamut(self, gamut_type: GamutType, gamut: ColorGamut) -> Self {
+        Self {
+            gamut: Some(gamut),
+            gamut_type,
+            ..self
+        }
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
@@ -406,6 +747,17 @@ impl From<ColorTemperature> for Option<ColorTemperatureUpdate> {
 }
 
 impl ColorTemperature {
+    /// Color temperature for lights that only support colors. Commands are
+    /// converted to the matching point on the black-body curve.
+    #[must_use]
+    pub const fn emulated() -> Self {
+        Self {
+            mirek: None,
+            mirek_schema: MirekSchema::DEFAULT,
+            mirek_valid: true,
+        }
+    }
+
     #[must_use]
     pub fn extract_from_expose(expose: &Expose) -> Option<Self> {
         let Expose::Numeric(num) = expose else {
@@ -450,3 +802,18 @@ impl Dimming {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hue_go_is_gamut_c() {
+        assert_eq!(ColorGamut::guess("philips", "LLC020").0, GamutType::C);
+    }
+
+    #[test]
+    fn living_colors_are_gamut_a() {
+        assert_eq!(ColorGamut::guess("philips", "LLC011").0, GamutType::A);
+    }
+}
@@ -1,31 +1,68 @@
+mod behavior;
 mod device;
+mod entertainment_config;
 mod grouped_light;
 mod light;
 mod resource;
 mod room;
 mod scene;
+mod sensor;
+mod smart_scene;
 mod stubs;
 mod update;
+mod zone;
 
-pub use device::{Device, DeviceArchetype, DeviceProductData};
+pub use behavior::{
+    BehaviorDuration, BehaviorInstance, BehaviorInstanceMetadata, BehaviorInstanceNew,
+    BehaviorInstanceStatus, BehaviorInstanceUpdate, BehaviorScript, BehaviorScriptMetadata,
+    BehaviorScriptType, BehaviorTimePoint, BehaviorWhen, BehaviorWhere, ComingHomeConfig,
+    ComingHomeWhat, GoToSleepConfig, TimerConfig, WakeUpConfig,
+};
+pub use device::{
+    Device, DeviceArchetype, DeviceIdentify, DeviceIdentifyAction, DeviceMetadataUpdate,
+    DeviceProductData, DeviceUpdate,
+};
+pub use entertainment_config::{
+    EntertainmentConfiguration, EntertainmentConfigurationAction,
+    EntertainmentConfigurationChannel, EntertainmentConfigurationChannelMember,
+    EntertainmentConfigurationLocations, EntertainmentConfigurationMetadata,
+    EntertainmentConfigurationNew, EntertainmentConfigurationServiceLocation,
+    EntertainmentConfigurationStatus, EntertainmentConfigurationStreamProxy,
+    EntertainmentConfigurationStreamProxyMode, EntertainmentConfigurationType,
+    EntertainmentConfigurationUpdate, Position,
+};
 pub use grouped_light::{GroupedLight, GroupedLightUpdate};
 pub use light::{
     ColorGamut, ColorTemperature, ColorTemperatureUpdate, ColorUpdate, Delta, Dimming,
-    DimmingUpdate, GamutType, Light, LightColor, LightUpdate, MirekSchema, On,
+    DimmingUpdate, DynamicsUpdate, GamutType, Light, LightAlert, LightAlertAction,
+    LightAlertUpdate, LightColor, LightEffect, LightEffects, LightEffectsUpdate, LightMode,
+    LightSignal, LightSignaling, LightSignalingUpdate, LightUpdate, MirekSchema, On,
 };
 pub use resource::{RType, ResourceLink, ResourceRecord};
-pub use room::{Room, RoomArchetype, RoomMetadata};
+pub use room::{Room, RoomArchetype, RoomMetadata, RoomMetadataUpdate, RoomUpdate};
 pub use scene::{
     Scene, SceneAction, SceneActionElement, SceneMetadata, SceneRecall, SceneStatus,
     SceneStatusUpdate, SceneUpdate,
 };
+pub use sensor::{
+    LightLevel, LightLevelData, LightLevelReport, LightLevelUpdate, Motion, MotionData,
+    MotionReport, MotionUpdate, Temperature, TemperatureData, TemperatureReport, TemperatureUpdate,
+};
+pub use smart_scene::{
+    SmartScene, SmartSceneActiveTimeslot, SmartSceneNew, SmartSceneRecall, SmartSceneRecallAction,
+    SmartSceneState, SmartSceneTime, SmartSceneTimeslot, SmartSceneTimeslotKind,
+    SmartSceneTimeslotStart, SmartSceneUpdate, SmartSceneWeekTimeslot, Weekday,
+};
 pub use stubs::{
-    BehaviorInstance, BehaviorScript, Bridge, BridgeHome, Button, ButtonData, ButtonMetadata,
-    ButtonReport, DollarRef, Entertainment, EntertainmentSegment, EntertainmentSegments,
-    GeofenceClient, Geolocation, Homekit, Matter, Metadata, PublicImage, SmartScene, TimeZone,
-    ZigbeeConnectivity, ZigbeeConnectivityStatus, ZigbeeDeviceDiscovery, Zone,
+    Bridge, BridgeHome, BridgeHomeUpdate, BridgeUpdate, Button, ButtonData, ButtonEvent,
+    ButtonMetadata, ButtonReport, ButtonUpdate, DollarRef, Entertainment, EntertainmentSegment,
+    EntertainmentSegments, GeofenceClient, GeofenceClientUpdate, Geolocation, Homekit, Matter,
+    Metadata, PublicImage, TimeZone, ZigbeeConnectivity, ZigbeeConnectivityStatus,
+    ZigbeeConnectivityUpdate, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryAction,
+    ZigbeeDeviceDiscoveryActionType, ZigbeeDeviceDiscoveryStatus, ZigbeeDeviceDiscoveryUpdate,
 };
 pub use update::{Update, UpdateRecord};
+pub use zone::{Zone, ZoneUpdate};
 
 use std::fmt::Debug;
 
@@ -45,16 +82,20 @@ pub enum Resource {
     Button(Button),
     Device(Device),
     Entertainment(Entertainment),
+    EntertainmentConfiguration(EntertainmentConfiguration),
     GeofenceClient(GeofenceClient),
     Geolocation(Geolocation),
     GroupedLight(GroupedLight),
     Homekit(Homekit),
     Light(Light),
+    LightLevel(LightLevel),
     Matter(Matter),
+    Motion(Motion),
     PublicImage(PublicImage),
     Room(Room),
     Scene(Scene),
     SmartScene(SmartScene),
+    Temperature(Temperature),
     ZigbeeConnectivity(ZigbeeConnectivity),
     ZigbeeDeviceDiscovery(ZigbeeDeviceDiscovery),
     Zone(Zone),
@@ -71,16 +112,20 @@ impl Resource {
             Self::Button(_) => RType::Button,
             Self::Device(_) => RType::Device,
             Self::Entertainment(_) => RType::Entertainment,
+            Self::EntertainmentConfiguration(_) => RType::EntertainmentConfiguration,
             Self::GeofenceClient(_) => RType::GeofenceClient,
             Self::Geolocation(_) => RType::Geolocation,
             Self::GroupedLight(_) => RType::GroupedLight,
             Self::Homekit(_) => RType::Homekit,
             Self::Light(_) => RType::Light,
+            Self::LightLevel(_) => RType::LightLevel,
             Self::Matter(_) => RType::Matter,
+            Self::Motion(_) => RType::Motion,
             Self::PublicImage(_) => RType::PublicImage,
             Self::Room(_) => RType::Room,
             Self::Scene(_) => RType::Scene,
             Self::SmartScene(_) => RType::SmartScene,
+            Self::Temperature(_) => RType::Temperature,
             Self::ZigbeeConnectivity(_) => RType::ZigbeeConnectivity,
             Self::ZigbeeDeviceDiscovery(_) => RType::ZigbeeDeviceDiscovery,
             Self::Zone(_) => RType::Zone,
@@ -96,16 +141,20 @@ impl Resource {
             RType::Button => Self::Button(from_value(obj)?),
             RType::Device => Self::Device(from_value(obj)?),
             RType::Entertainment => Self::Entertainment(from_value(obj)?),
+            RType::EntertainmentConfiguration => Self::EntertainmentConfiguration(from_value(obj)?),
             RType::GeofenceClient => Self::GeofenceClient(from_value(obj)?),
             RType::Geolocation => Self::Geolocation(from_value(obj)?),
             RType::GroupedLight => Self::GroupedLight(from_value(obj)?),
             RType::Homekit => Self::Homekit(from_value(obj)?),
             RType::Light => Self::Light(from_value(obj)?),
+            RType::LightLevel => Self::LightLevel(from_value(obj)?),
             RType::Matter => Self::Matter(from_value(obj)?),
+            RType::Motion => Self::Motion(from_value(obj)?),
             RType::PublicImage => Self::PublicImage(from_value(obj)?),
             RType::Room => Self::Room(from_value(obj)?),
             RType::Scene => Self::Scene(from_value(obj)?),
             RType::SmartScene => Self::SmartScene(from_value(obj)?),
+            RType::Temperature => Self::Temperature(from_value(obj)?),
             RType::ZigbeeConnectivity => Self::ZigbeeConnectivity(from_value(obj)?),
             RType::ZigbeeDeviceDiscovery => Self::ZigbeeDeviceDiscovery(from_value(obj)?),
             RType::Zone => Self::Zone(from_value(obj)?),
@@ -168,16 +217,20 @@ resource_conversion_impl!(BridgeHome);
 resource_conversion_impl!(Button);
 resource_conversion_impl!(Device);
 resource_conversion_impl!(Entertainment);
+resource_conversion_impl!(EntertainmentConfiguration);
 resource_conversion_impl!(GeofenceClient);
 resource_conversion_impl!(Geolocation);
 resource_conversion_impl!(GroupedLight);
 resource_conversion_impl!(Homekit);
 resource_conversion_impl!(Light);
+resource_conversion_impl!(LightLevel);
 resource_conversion_impl!(Matter);
+resource_conversion_impl!(Motion);
 resource_conversion_impl!(PublicImage);
 resource_conversion_impl!(Room);
 resource_conversion_impl!(Scene);
 resource_conversion_impl!(SmartScene);
+resource_conversion_impl!(Temperature);
 resource_conversion_impl!(ZigbeeConnectivity);
 resource_conversion_impl!(ZigbeeDeviceDiscovery);
 resource_conversion_impl!(Zone);
@@ -216,8 +269,13 @@ impl<'a> V1Reply<'a> {
     pub fn with_light_state_update(self, upd: &ApiLightStateUpdate) -> ApiResult<Self> {
         self.add_option("on", upd.on)?
             .add_option("bri", upd.bri)?
+            .add_option("hue", upd.hue)?
+            .add_option("sat", upd.sat)?
             .add_option("xy", upd.xy)?
-            .add_option("ct", upd.ct)
+            .add_option("ct", upd.ct)?
+            .add_option("transitiontime", upd.transitiontime)?
+            .add_option("alert", upd.alert)?
+            .add_option("effect", upd.effect)
     }
 
     pub fn add<T: Serialize>(mut self, name: &'a str, value: T) -> ApiResult<Self> {
@@ -16,16 +16,20 @@ pub enum RType {
     Button,
     Device,
     Entertainment,
+    EntertainmentConfiguration,
     GeofenceClient,
     Geolocation,
     GroupedLight,
     Homekit,
     Light,
+    LightLevel,
     Matter,
+    Motion,
     PublicImage,
     Room,
     Scene,
     SmartScene,
+    Temperature,
     ZigbeeConnectivity,
     ZigbeeDeviceDiscovery,
     Zone,
@@ -79,3 +79,30 @@ impl RoomMetadata {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RoomUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RoomMetadataUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ResourceLink>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RoomMetadataUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archetype: Option<RoomArchetype>,
+}
+
+impl RoomMetadata {
+    pub fn apply(&mut self, upd: RoomMetadataUpdate) {
+        if let Some(name) = upd.name {
+            self.name = name;
+        }
+        if let Some(archetype) = upd.archetype {
+            self.archetype = archetype;
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use std::ops::AddAssign;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::hue::api::{ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, On, ResourceLink};
+use crate::hue::api::{
+    ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, Light, On, ResourceLink,
+};
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(tag = "active", rename_all = "snake_case")]
@@ -58,6 +62,46 @@ pub struct SceneAction {
     pub on: Option<On>,
 }
 
+impl From<&Light> for SceneAction {
+    /// The current state of a light, as a scene action
+    fn from(light: &Light) -> Self {
+        let color_temperature = light.as_mirek_opt().map(ColorTemperatureUpdate::new);
+        let color = if color_temperature.is_some() {
+            None
+        } else {
+            light.as_color_opt().map(ColorUpdate::new)
+        };
+
+        Self {
+            color,
+            color_temperature,
+            dimming: light.as_dimming_opt(),
+            on: Some(light.on),
+        }
+    }
+}
+
+impl AddAssign<Self> for SceneAction {
+    fn add_assign(&mut self, upd: Self) {
+        if upd.on.is_some() {
+            self.on = upd.on;
+        }
+
+        if upd.dimming.is_some() {
+            self.dimming = upd.dimming;
+        }
+
+        /* color and color temperature are mutually exclusive */
+        if upd.color.is_some() {
+            self.color = upd.color;
+            self.color_temperature = None;
+        } else if upd.color_temperature.is_some() {
+            self.color_temperature = upd.color_temperature;
+            self.color = None;
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SceneActionElement {
     pub action: SceneAction,
@@ -113,6 +157,15 @@ impl SceneUpdate {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SceneRecall {
     pub action: Option<SceneStatusUpdate>,
+    /// Transition time, in milliseconds
     pub duration: Option<u32>,
     pub dimming: Option<DimmingUpdate>,
 }
+
+impl SceneRecall {
+    /// Transition time in seconds, as used by zigbee2mqtt
+    #[must_use]
+    pub fn transition(&self) -> Option<f64> {
+        self.duration.map(|ms| f64::from(ms) / 1000.0)
+    }
+}
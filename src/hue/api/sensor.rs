@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::hue::api::ResourceLink;
+use crate::hue::date_format;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Motion {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub motion: MotionData,
+}
+
+impl Motion {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            motion: MotionData {
+                motion: false,
+                motion_valid: false,
+                motion_report: None,
+            },
+        }
+    }
+
+    pub fn report(&mut self, motion: bool) {
+        self.motion = MotionData {
+            motion,
+            motion_valid: true,
+            motion_report: Some(MotionReport {
+                changed: Utc::now(),
+                motion,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MotionData {
+    pub motion: bool,
+    pub motion_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub motion_report: Option<MotionReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MotionReport {
+    #[serde(with = "date_format::utc")]
+    pub changed: DateTime<Utc>,
+    pub motion: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MotionUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub motion: Option<MotionData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightLevel {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub light: LightLevelData,
+}
+
+impl LightLevel {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            light: LightLevelData {
+                light_level: 0,
+                light_level_valid: false,
+                light_level_report: None,
+            },
+        }
+    }
+
+    /// Convert an illuminance measurement (in lux) to the logarithmic
+    /// scale used by hue (10000 * log10(lux) + 1)
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn lux_to_light_level(lux: f64) -> u32 {
+        if lux <= 0.0 {
+            0
+        } else {
+            10000.0f64.mul_add(lux.log10(), 1.0).round().max(0.0) as u32
+        }
+    }
+
+    pub fn report(&mut self, lux: f64) {
+        let light_level = Self::lux_to_light_level(lux);
+        self.light = LightLevelData {
+            light_level,
+            light_level_valid: true,
+            light_level_report: Some(LightLevelReport {
+                changed: Utc::now(),
+                light_level,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightLevelData {
+    pub light_level: u32,
+    pub light_level_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light_level_report: Option<LightLevelReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightLevelReport {
+    #[serde(with = "date_format::utc")]
+    pub changed: DateTime<Utc>,
+    pub light_level: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LightLevelUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light: Option<LightLevelData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Temperature {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub temperature: TemperatureData,
+}
+
+impl Temperature {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            temperature: TemperatureData {
+                temperature: 0.0,
+                temperature_valid: false,
+                temperature_report: None,
+            },
+        }
+    }
+
+    pub fn report(&mut self, temperature: f64) {
+        self.temperature = TemperatureData {
+            temperature,
+            temperature_valid: true,
+            temperature_report: Some(TemperatureReport {
+                changed: Utc::now(),
+                temperature,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemperatureData {
+    pub temperature: f64,
+    pub temperature_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_report: Option<TemperatureReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemperatureReport {
+    #[serde(with = "date_format::utc")]
+    pub changed: DateTime<Utc>,
+    pub temperature: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TemperatureUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<TemperatureData>,
+}
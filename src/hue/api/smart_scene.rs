@@ -0,0 +1,182 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::hue::api::{ResourceLink, SceneMetadata};
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneState {
+    Active,
+    Inactive,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(value: chrono::Weekday) -> Self {
+        match value {
+            chrono::Weekday::Mon => Self::Monday,
+            chrono::Weekday::Tue => Self::Tuesday,
+            chrono::Weekday::Wed => Self::Wednesday,
+            chrono::Weekday::Thu => Self::Thursday,
+            chrono::Weekday::Fri => Self::Friday,
+            chrono::Weekday::Sat => Self::Saturday,
+            chrono::Weekday::Sun => Self::Sunday,
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartSceneActiveTimeslot {
+    pub timeslot_id: u32,
+    pub weekday: Weekday,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneTimeslotKind {
+    Time,
+    Sunrise,
+    Sunset,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartSceneTime {
+    pub hour: u32,
+    pub minute: u32,
+    #[serde(default)]
+    pub second: u32,
+}
+
+impl SmartSceneTime {
+    #[must_use]
+    pub const fn as_naive_time(&self) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(self.hour, self.minute, self.second)
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartSceneTimeslotStart {
+    pub kind: SmartSceneTimeslotKind,
+    /// Start time for `time` slots. For sun-relative slots, this is used as a
+    /// fallback if sunrise/sunset cannot be calculated.
+    pub time: SmartSceneTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartSceneTimeslot {
+    pub start_time: SmartSceneTimeslotStart,
+    pub target: ResourceLink,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartSceneWeekTimeslot {
+    pub timeslots: Vec<SmartSceneTimeslot>,
+    pub recurrence: Vec<Weekday>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartScene {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_timeslot: Option<SmartSceneActiveTimeslot>,
+    pub group: ResourceLink,
+    pub metadata: SceneMetadata,
+    pub state: SmartSceneState,
+    #[serde(default = "SmartScene::default_transition_duration")]
+    pub transition_duration: u32,
+    pub week_timeslots: Vec<SmartSceneWeekTimeslot>,
+}
+
+impl SmartScene {
+    const fn default_transition_duration() -> u32 {
+        60_000
+    }
+
+    /// Find the timeslot (and its target scene) that is in effect at `now`.
+    ///
+    /// The `sun` function returns the local sunrise and sunset times for a
+    /// given date, if known.
+    #[must_use]
+    pub fn timeslot_at(
+        &self,
+        now: NaiveDateTime,
+        sun: impl Fn(NaiveDate) -> Option<(NaiveTime, NaiveTime)>,
+    ) -> Option<(SmartSceneActiveTimeslot, ResourceLink)> {
+        /* the slot in effect might have started on any of the past 7 days */
+        (0..=7)
+            .filter_map(|days| now.date().checked_sub_signed(Duration::days(days)))
+            .flat_map(|date| {
+                let weekday = Weekday::from(date.weekday());
+                let sun_times = sun(date);
+                self.week_timeslots
+                    .iter()
+                    .filter(move |wt| wt.recurrence.contains(&weekday))
+                    .flat_map(|wt| wt.timeslots.iter().enumerate())
+                    .filter_map(move |(index, slot)| {
+                        let start = slot.start_time;
+                        let time = match (start.kind, sun_times) {
+                            (SmartSceneTimeslotKind::Sunrise, Some((rise, _))) => rise,
+                            (SmartSceneTimeslotKind::Sunset, Some((_, set))) => set,
+                            _ => start.time.as_naive_time()?,
+                        };
+                        let active = SmartSceneActiveTimeslot {
+                            timeslot_id: u32::try_from(index).ok()?,
+                            weekday,
+                        };
+                        Some((date.and_time(time), active, slot.target))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(start, _, _)| *start <= now)
+            .max_by_key(|(start, _, _)| *start)
+            .map(|(_, active, target)| (active, target))
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneRecallAction {
+    Activate,
+    Deactivate,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+pub struct SmartSceneRecall {
+    pub action: SmartSceneRecallAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartSceneNew {
+    pub group: ResourceLink,
+    pub metadata: SceneMetadata,
+    #[serde(default = "SmartScene::default_transition_duration")]
+    pub transition_duration: u32,
+    pub week_timeslots: Vec<SmartSceneWeekTimeslot>,
+    pub recall: Option<SmartSceneRecall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SmartSceneUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<SceneMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_timeslots: Option<Vec<SmartSceneWeekTimeslot>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_duration: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recall: Option<SmartSceneRecall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<SmartSceneState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_timeslot: Option<SmartSceneActiveTimeslot>,
+}
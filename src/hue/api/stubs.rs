@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::hue::api::{DeviceArchetype, ResourceLink, SceneMetadata};
+use crate::hue::api::{DeviceArchetype, DeviceMetadataUpdate, ResourceLink};
 use crate::hue::{best_guess_timezone, date_format};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,12 +12,26 @@ pub struct Bridge {
     pub time_zone: TimeZone,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BridgeUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<TimeZone>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BridgeHome {
     pub children: Vec<ResourceLink>,
     pub services: Vec<ResourceLink>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BridgeHomeUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ResourceLink>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<ResourceLink>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Button {
     pub owner: ResourceLink,
@@ -37,36 +51,38 @@ pub struct ButtonData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repeat_interval: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub event_values: Option<Value>,
+    pub event_values: Option<Vec<ButtonEvent>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ButtonReport {
     #[serde(with = "date_format::utc")]
     pub updated: DateTime<Utc>,
-    pub event: String,
+    pub event: ButtonEvent,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DollarRef {
-    #[serde(rename = "$ref")]
-    pub dref: String,
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonEvent {
+    InitialPress,
+    Repeat,
+    ShortRelease,
+    LongRelease,
+    DoubleShortRelease,
+    LongPress,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BehaviorScript {
-    pub configuration_schema: DollarRef,
-    pub description: String,
-    pub max_number_instances: Option<u32>,
-    pub metadata: Value,
-    pub state_schema: DollarRef,
-    pub supported_features: Vec<String>,
-    pub trigger_schema: DollarRef,
-    pub version: String,
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ButtonUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub button: Option<ButtonData>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BehaviorInstance {}
+pub struct DollarRef {
+    #[serde(rename = "$ref")]
+    pub dref: String,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Entertainment {
@@ -74,10 +90,33 @@ pub struct Entertainment {
     pub owner: ResourceLink,
     pub proxy: bool,
     pub renderer: bool,
-    pub renderer_reference: ResourceLink,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renderer_reference: Option<ResourceLink>,
     pub segments: EntertainmentSegments,
 }
 
+impl Entertainment {
+    /// Entertainment service for a single-segment light
+    #[must_use]
+    pub fn for_light(owner: ResourceLink, light: ResourceLink) -> Self {
+        Self {
+            equalizer: true,
+            owner,
+            proxy: false,
+            renderer: true,
+            renderer_reference: Some(light),
+            segments: EntertainmentSegments {
+                configurable: false,
+                max_segments: 1,
+                segments: vec![EntertainmentSegment {
+                    length: 1,
+                    start: 0,
+                }],
+            },
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EntertainmentSegments {
     pub configurable: bool,
@@ -94,6 +133,16 @@ pub struct EntertainmentSegment {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeofenceClient {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_at_home: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GeofenceClientUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_at_home: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -129,47 +178,88 @@ pub struct Matter {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PublicImage {}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SmartScene {
-    /* active_timeslot: { */
-    /*     timeslot_id: 3, */
-    /*     weekday: monday */
-    /* }, */
-    pub active_timeslot: Value,
-    pub group: ResourceLink,
-    pub metadata: SceneMetadata,
-    pub state: String,
-    pub transition_duration: u32,
-    pub week_timeslots: Value,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ZigbeeConnectivityStatus {
+    Connected,
+    Disconnected,
     ConnectivityIssue,
+    UnidirectionalIncoming,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ZigbeeConnectivity {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub channel: Option<Value>,
-    pub extended_pan_id: String,
+    /* only reported for the bridge itself */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_pan_id: Option<String>,
     pub mac_address: String,
     pub owner: ResourceLink,
     pub status: ZigbeeConnectivityStatus,
 }
 
+impl ZigbeeConnectivity {
+    #[must_use]
+    pub const fn new(owner: ResourceLink, mac_address: String) -> Self {
+        Self {
+            channel: None,
+            extended_pan_id: None,
+            mac_address,
+            owner,
+            status: ZigbeeConnectivityStatus::Connected,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ZigbeeConnectivityUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_pan_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ZigbeeConnectivityStatus>,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZigbeeDeviceDiscoveryStatus {
+    Ready,
+    Searching,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ZigbeeDeviceDiscovery {
     pub owner: ResourceLink,
-    pub status: String,
+    pub status: ZigbeeDeviceDiscoveryStatus,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZigbeeDeviceDiscoveryActionType {
+    Search,
+    SearchAllowDefaultLinkKey,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Zone {
-    pub metadata: Metadata,
-    pub children: Vec<ResourceLink>,
-    #[serde(default)]
-    pub services: Vec<ResourceLink>,
+pub struct ZigbeeDeviceDiscoveryAction {
+    pub action_type: ZigbeeDeviceDiscoveryActionType,
+    /* install codes and search codes are not supported by zigbee2mqtt */
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_codes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub install_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ZigbeeDeviceDiscoveryUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<ZigbeeDeviceDiscoveryAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ZigbeeDeviceDiscoveryStatus>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -200,4 +290,13 @@ impl Metadata {
             name: name.to_string(),
         }
     }
+
+    pub fn apply(&mut self, upd: &DeviceMetadataUpdate) {
+        if let Some(name) = &upd.name {
+            self.name.clone_from(name);
+        }
+        if let Some(archetype) = &upd.archetype {
+            self.archetype = archetype.clone();
+        }
+    }
 }
@@ -1,48 +1,87 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::hue::api::{GroupedLightUpdate, LightUpdate, RType, SceneUpdate};
+use crate::hue::api::{
+    BehaviorInstanceUpdate, BridgeHomeUpdate, BridgeUpdate, ButtonUpdate, DeviceUpdate,
+    EntertainmentConfigurationUpdate, GeofenceClientUpdate, GroupedLightUpdate, LightLevelUpdate,
+    LightUpdate, MotionUpdate, RType, RoomUpdate, SceneUpdate, SmartSceneUpdate, TemperatureUpdate,
+    ZigbeeConnectivityUpdate, ZigbeeDeviceDiscoveryUpdate, ZoneUpdate,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Update {
     /* BehaviorScript(BehaviorScriptUpdate), */
-    /* BehaviorInstance(BehaviorInstanceUpdate), */
-    /* Bridge(BridgeUpdate), */
-    /* BridgeHome(BridgeHomeUpdate), */
-    /* Device(DeviceUpdate), */
+    BehaviorInstance(BehaviorInstanceUpdate),
+    Bridge(BridgeUpdate),
+    BridgeHome(BridgeHomeUpdate),
+    Button(ButtonUpdate),
+    Device(DeviceUpdate),
     /* Entertainment(EntertainmentUpdate), */
-    /* GeofenceClient(GeofenceClientUpdate), */
+    EntertainmentConfiguration(EntertainmentConfigurationUpdate),
+    GeofenceClient(GeofenceClientUpdate),
     /* Geolocation(GeolocationUpdate), */
     GroupedLight(GroupedLightUpdate),
     /* Homekit(HomekitUpdate), */
     Light(LightUpdate),
+    LightLevel(LightLevelUpdate),
     /* Matter(MatterUpdate), */
+    Motion(MotionUpdate),
     /* PublicImage(PublicImageUpdate), */
-    /* Room(RoomUpdate), */
+    Room(RoomUpdate),
     Scene(SceneUpdate),
-    /* SmartScene(SmartSceneUpdate), */
-    /* ZigbeeConnectivity(ZigbeeConnectivityUpdate), */
-    /* ZigbeeDeviceDiscovery(ZigbeeDeviceDiscoveryUpdate), */
-    /* Zone(ZoneUpdate), */
+    SmartScene(SmartSceneUpdate),
+    Temperature(TemperatureUpdate),
+    ZigbeeConnectivity(ZigbeeConnectivityUpdate),
+    ZigbeeDeviceDiscovery(ZigbeeDeviceDiscoveryUpdate),
+    Zone(ZoneUpdate),
 }
 
 impl Update {
     #[must_use]
     pub const fn rtype(&self) -> RType {
         match self {
+            Self::BehaviorInstance(_) => RType::BehaviorInstance,
+            Self::Bridge(_) => RType::Bridge,
+            Self::BridgeHome(_) => RType::BridgeHome,
+            Self::Button(_) => RType::Button,
+            Self::Device(_) => RType::Device,
+            Self::EntertainmentConfiguration(_) => RType::EntertainmentConfiguration,
+            Self::GeofenceClient(_) => RType::GeofenceClient,
             Self::GroupedLight(_) => RType::GroupedLight,
             Self::Light(_) => RType::Light,
+            Self::LightLevel(_) => RType::LightLevel,
+            Self::Motion(_) => RType::Motion,
+            Self::Room(_) => RType::Room,
             Self::Scene(_) => RType::Scene,
+            Self::SmartScene(_) => RType::SmartScene,
+            Self::Temperature(_) => RType::Temperature,
+            Self::ZigbeeConnectivity(_) => RType::ZigbeeConnectivity,
+            Self::ZigbeeDeviceDiscovery(_) => RType::ZigbeeDeviceDiscovery,
+            Self::Zone(_) => RType::Zone,
         }
     }
 
     #[must_use]
     pub fn id_v1_scope(&self, id: u32, uuid: &Uuid) -> Option<String> {
         match self {
-            Self::GroupedLight(_) => Some(format!("/groups/{id}")),
+            Self::GroupedLight(_) | Self::Room(_) => Some(format!("/groups/{id}")),
             Self::Light(_) => Some(format!("/lights/{id}")),
             Self::Scene(_) => Some(format!("/scenes/{uuid}")),
+            Self::LightLevel(_) | Self::Motion(_) | Self::Temperature(_) => {
+                Some(format!("/sensors/{id}"))
+            }
+            Self::BehaviorInstance(_)
+            | Self::Bridge(_)
+            | Self::BridgeHome(_)
+            | Self::Button(_)
+            | Self::Device(_)
+            | Self::EntertainmentConfiguration(_)
+            | Self::GeofenceClient(_)
+            | Self::SmartScene(_)
+            | Self::ZigbeeConnectivity(_)
+            | Self::ZigbeeDeviceDiscovery(_)
+            | Self::Zone(_) => None,
         }
     }
 }
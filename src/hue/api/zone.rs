@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::hue::api::{RType, ResourceLink, RoomMetadata, RoomMetadataUpdate};
+
+/// A zone is a free-form grouping of lights. Unlike rooms, zones can overlap,
+/// and their children are light services (not devices).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Zone {
+    pub metadata: RoomMetadata,
+    pub children: Vec<ResourceLink>,
+    #[serde(default)]
+    pub services: Vec<ResourceLink>,
+}
+
+impl Zone {
+    #[must_use]
+    pub fn grouped_light_service(&self) -> Option<&ResourceLink> {
+        self.services
+            .iter()
+            .find(|rl| rl.rtype == RType::GroupedLight)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ZoneUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RoomMetadataUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ResourceLink>>,
+}
@@ -17,7 +17,8 @@ pub mod utc {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let dt = NaiveDateTime::parse_from_str(&s, super::FORMAT).map_err(Error::custom)?;
+        let dt = NaiveDateTime::parse_from_str(s.trim_end_matches('Z'), super::FORMAT)
+            .map_err(Error::custom)?;
         Ok(DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
     }
 }
@@ -1,13 +1,17 @@
-use std::{collections::HashMap, net::Ipv4Addr};
+use std::collections::{BTreeMap, HashMap};
+use std::net::Ipv4Addr;
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Local, NaiveDateTime, Timelike, Utc};
 use mac_address::MacAddress;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::error::ApiResult;
+use crate::hue::timepattern::TimePattern;
 use crate::hue::{api, best_guess_timezone};
+use crate::model::color::HS;
+use crate::model::types::XY;
 use crate::resource::Resources;
 
 use super::date_format;
@@ -20,6 +24,27 @@ pub struct HueError {
     description: String,
 }
 
+impl HueError {
+    #[must_use]
+    pub fn new(typ: u32, address: &str, description: &str) -> Self {
+        Self {
+            typ,
+            address: address.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn unauthorized_user(address: &str) -> Self {
+        Self::new(1, address, "unauthorized user")
+    }
+
+    #[must_use]
+    pub fn link_button_not_pressed() -> Self {
+        Self::new(101, "", "link button not pressed")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum HueResult<T> {
@@ -58,7 +83,7 @@ impl Default for ApiShortConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiResourceType {
     Config,
@@ -72,16 +97,36 @@ pub enum ApiResourceType {
     Capabilities,
 }
 
+impl ApiResourceType {
+    /// Name of the resource type, as used in v1 api paths
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Groups => "groups",
+            Self::Lights => "lights",
+            Self::Resourcelinks => "resourcelinks",
+            Self::Rules => "rules",
+            Self::Scenes => "scenes",
+            Self::Schedules => "schedules",
+            Self::Sensors => "sensors",
+            Self::Capabilities => "capabilities",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewUser {
-    devicetype: String,
-    generateclientkey: Option<bool>,
+    pub devicetype: String,
+    pub generateclientkey: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewUserReply {
     pub username: Uuid,
-    pub clientkey: Uuid,
+    /// Pre-shared key for entertainment streaming, as 32 uppercase hex digits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clientkey: Option<String>,
 }
 
 #[allow(non_camel_case_types)]
@@ -234,15 +279,47 @@ pub struct ApiConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ApiConfigUpdate {
+    pub linkbutton: Option<bool>,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiEffect {
     None,
+    Colorloop,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ApiEffect {
+    /// Name of this effect in zigbee2mqtt
+    #[must_use]
+    pub const fn z2m_effect(self) -> &'static str {
+        match self {
+            Self::None => "stop_effect",
+            Self::Colorloop => "colorloop",
+        }
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiAlert {
     None,
+    Select,
+    Lselect,
+}
+
+impl ApiAlert {
+    /// Name of this alert in zigbee2mqtt ("select" is a single flash). Many
+    /// clients send "none" with every request, so it does nothing.
+    #[must_use]
+    pub const fn z2m_effect(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Select => Some("blink"),
+            Self::Lselect => Some("breathe"),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -341,14 +418,86 @@ pub struct ApiLightStateUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bri: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub hue: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sat: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub xy: Option<[f64; 2]>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ct: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitiontime: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<ApiAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect: Option<ApiEffect>,
+}
+
+impl ApiLightStateUpdate {
+    /// The z2m effect to trigger, if any (effects take priority over alerts)
+    #[must_use]
+    pub fn z2m_effect(&self) -> Option<&'static str> {
+        self.effect
+            .map(ApiEffect::z2m_effect)
+            .or_else(|| self.alert.and_then(ApiAlert::z2m_effect))
+    }
+
+    /// Transition time in seconds (v1 uses multiples of 100ms)
+    #[must_use]
+    pub fn transition(&self) -> Option<f64> {
+        self.transitiontime.map(|ds| f64::from(ds) / 10.0)
+    }
+
+    /// This update as a scene action. A lone hue or saturation is applied
+    /// to the `current` color (see [`Self::color_xy`]).
+    #[must_use]
+    pub fn scene_action(&self, current: Option<XY>) -> api::SceneAction {
+        api::SceneAction {
+            color: self.color_xy(current).map(api::ColorUpdate::new),
+            color_temperature: self.ct.map(api::ColorTemperatureUpdate::new),
+            dimming: self
+                .bri
+                .map(|bri| api::DimmingUpdate::new(f64::from(bri) / 2.54)),
+            on: self.on.map(api::On::new),
+        }
+    }
+
+    /// The requested color as xy. When only one of hue and saturation is
+    /// given, the other is taken from the `current` color.
+    #[must_use]
+    pub fn color_xy(&self, current: Option<XY>) -> Option<XY> {
+        if let Some(xy) = self.xy {
+            return Some(xy.into());
+        }
+
+        if self.hue.is_none() && self.sat.is_none() {
+            return None;
+        }
+
+        let current = current.unwrap_or(XY::D65_WHITE_POINT).to_hs();
+        let hs = HS::new(
+            self.hue
+                .map_or(current.hue, |hue| f64::from(hue) * 360.0 / 65535.0),
+            self.sat.map_or(current.sat, |sat| f64::from(sat) / 254.0),
+        );
+
+        Some(XY::from_hs(hs))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiGroupUpdate {
     pub scene: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transitiontime: Option<u16>,
+}
+
+impl ApiGroupUpdate {
+    /// Transition time in seconds (v1 uses multiples of 100ms)
+    #[must_use]
+    pub fn transition(&self) -> Option<f64> {
+        self.transitiontime.map(|ds| f64::from(ds) / 10.0)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -364,8 +513,13 @@ impl From<api::SceneAction> for ApiLightStateUpdate {
         Self {
             on: action.on.map(|on| on.on),
             bri: action.dimming.map(|dim| (dim.brightness * 2.54) as u32),
+            hue: None,
+            sat: None,
             xy: action.color.map(|col| col.xy.into()),
             ct: action.color_temperature.map(|ct| ct.mirek),
+            transitiontime: None,
+            alert: None,
+            effect: None,
         }
     }
 }
@@ -392,12 +546,42 @@ impl ApiLight {
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     #[must_use]
     pub fn from_dev_and_light(uuid: &Uuid, dev: &api::Device, light: &api::Light) -> Self {
+        let light_type = match (&light.color, &light.color_temperature, &light.dimming) {
+            (Some(_), Some(_), _) => "Extended color light",
+            (Some(_), None, _) => "Color light",
+            (None, Some(_), _) => "Color temperature light",
+            (None, None, Some(_)) => "Dimmable light",
+            (None, None, None) => "On/Off plug-in unit",
+        };
+
+        let (gamut_type, gamut) = light
+            .color
+            .as_ref()
+            .and_then(|col| Some((col.gamut_type, col.gamut.clone()?)))
+            .unwrap_or((api::GamutType::C, api::ColorGamut::GAMUT_C));
+        let gamut_type = match gamut_type {
+            api::GamutType::A => "A",
+            api::GamutType::B => "B",
+            api::GamutType::C => "C",
+            api::GamutType::Other => "other",
+        };
+
         let colormode = if light.color.is_some() {
             LightColorMode::Xy
         } else {
             LightColorMode::Ct
         };
 
+        /* v1 clients expect hue/saturation and color temperature to be
+         * reported, even for lights that are in xy mode */
+        let hs = light
+            .color
+            .as_ref()
+            .map_or(HS::new(0.0, 0.0), |col| col.xy.to_hs());
+        let ct = light
+            .as_mirek_opt()
+            .or_else(|| light.color.as_ref().map(|col| col.xy.to_mirek(153, 500)));
+
         let product_data = dev.product_data.clone();
 
         Self {
@@ -407,19 +591,15 @@ impl ApiLight {
                     .dimming
                     .map(|dim| (dim.brightness * 2.54) as u32)
                     .unwrap_or_default(),
-                hue: 0,
-                sat: 0,
+                hue: (hs.hue / 360.0 * 65535.0) as u32,
+                sat: (hs.sat * 254.0) as u32,
                 effect: String::new(),
                 xy: light
                     .color
                     .clone()
                     .map(|col| col.xy.into())
                     .unwrap_or_default(),
-                ct: light
-                    .color_temperature
-                    .clone()
-                    .and_then(|ct| ct.mirek)
-                    .unwrap_or_default(),
+                ct: ct.unwrap_or_default(),
                 alert: String::new(),
                 colormode,
                 mode: "homeautomation".to_string(),
@@ -435,11 +615,11 @@ impl ApiLight {
                 "certified": true,
                 "control": {
                     "colorgamut": [
-                        [0.6915, 0.3083 ],
-                        [0.17,   0.7    ],
-                        [0.1532, 0.0475 ],
+                        [gamut.red.x, gamut.red.y],
+                        [gamut.green.x, gamut.green.y],
+                        [gamut.blue.x, gamut.blue.y],
                     ],
-                    "colorgamuttype": "C",
+                    "colorgamuttype": gamut_type,
                     "ct": {
                         "max": 500,
                         "min": 153
@@ -453,7 +633,7 @@ impl ApiLight {
                 }
             }),
             config: json!({
-                "archetype": "spotbulb",
+                "archetype": dev.metadata.archetype,
                 "function": "mixed",
                 "direction": "downwards",
                 "startup": {
@@ -461,21 +641,152 @@ impl ApiLight {
                     "configured": true
                 }
             }),
-            light_type: "Extended color light".to_string(),
+            light_type: light_type.to_string(),
             uniqueid: uuid.as_simple().to_string(),
             swversion: product_data.software_version,
             swconfigid: String::new(),
         }
     }
+
+    #[must_use]
+    pub const fn with_reachable(mut self, reachable: bool) -> Self {
+        self.state.reachable = reachable;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiStatus {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ApiCommandMethod {
+    Put,
+    Post,
+    Delete,
+}
+
+/// A v1 api request, embedded in schedules and rules
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiCommand {
+    pub address: String,
+    pub method: ApiCommandMethod,
+    #[serde(default)]
+    pub body: Value,
+}
+
+impl ApiCommand {
+    /// The address relative to the user, e.g. `/api/<user>/lights/1/state`
+    /// becomes `["lights", "1", "state"]`
+    #[must_use]
+    pub fn path(&self) -> Vec<&str> {
+        let mut parts: Vec<&str> = self.address.split('/').filter(|p| !p.is_empty()).collect();
+        if parts.first() == Some(&"api") {
+            parts.drain(..2.min(parts.len()));
+        }
+        parts
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ApiResourceLink {}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiResourceLink {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub link_type: String,
+    pub classid: u32,
+    pub owner: String,
+    pub recycle: bool,
+    pub links: Vec<String>,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ApiRule {}
+pub struct ApiResourceLinkNew {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub classid: u32,
+    #[serde(default)]
+    pub recycle: bool,
+    #[serde(default)]
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiResourceLinkUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub classid: Option<u32>,
+    pub recycle: Option<bool>,
+    pub links: Option<Vec<String>>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiRuleOperator {
+    Eq,
+    Gt,
+    Lt,
+    Dx,
+    Ddx,
+    Stable,
+    #[serde(rename = "not stable")]
+    NotStable,
+    In,
+    #[serde(rename = "not in")]
+    NotIn,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiRuleCondition {
+    pub address: String,
+    pub operator: ApiRuleOperator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiRule {
+    pub name: String,
+    pub owner: String,
+    #[serde(with = "date_format::utc")]
+    pub created: DateTime<Utc>,
+    /// Time of the last trigger, or "none"
+    pub lasttriggered: String,
+    pub timestriggered: u32,
+    pub status: ApiStatus,
+    pub recycle: bool,
+    pub conditions: Vec<ApiRuleCondition>,
+    pub actions: Vec<ApiCommand>,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ApiRuleNew {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub status: ApiStatus,
+    #[serde(default)]
+    pub recycle: bool,
+    pub conditions: Vec<ApiRuleCondition>,
+    pub actions: Vec<ApiCommand>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiRuleUpdate {
+    pub name: Option<String>,
+    pub status: Option<ApiStatus>,
+    pub recycle: Option<bool>,
+    pub conditions: Option<Vec<ApiRuleCondition>>,
+    pub actions: Option<Vec<ApiCommand>>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum ApiSceneType {
     LightScene,
     GroupScene,
@@ -486,12 +797,44 @@ pub enum ApiSceneVersion {
     V2 = 2,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApiSceneAppData {
     pub data: String,
     pub version: u8,
 }
 
+/// Scene attributes that only exist in the v1 api, stored for scenes
+/// created or modified through it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiSceneData {
+    #[serde(rename = "type")]
+    pub scene_type: ApiSceneType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub recycle: bool,
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appdata: Option<ApiSceneAppData>,
+    pub picture: String,
+    #[serde(with = "date_format::utc")]
+    pub lastupdated: DateTime<Utc>,
+}
+
+impl ApiSceneData {
+    #[must_use]
+    pub fn new(scene_type: ApiSceneType) -> Self {
+        Self {
+            scene_type,
+            owner: None,
+            recycle: false,
+            locked: false,
+            appdata: None,
+            picture: String::new(),
+            lastupdated: Utc::now(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiScene {
     name: String,
@@ -512,7 +855,12 @@ pub struct ApiScene {
 }
 
 impl ApiScene {
-    pub fn from_scene(res: &Resources, owner: &String, scene: &api::Scene) -> ApiResult<Self> {
+    pub fn from_scene(
+        res: &Resources,
+        owner: &String,
+        uuid: &Uuid,
+        scene: &api::Scene,
+    ) -> ApiResult<Self> {
         let lights = scene
             .actions
             .iter()
@@ -532,7 +880,13 @@ impl ApiScene {
 
         let room_id = res.get_id_v1_index(scene.group.rid)?;
 
-        Ok(Self {
+        /* Some clients (e.g. Hue Essentials) require .appdata */
+        let default_appdata = ApiSceneAppData {
+            data: format!("xxxxx_r{room_id}"),
+            version: 1,
+        };
+
+        let mut api_scene = Self {
             name: scene.metadata.name.clone(),
             scene_type: ApiSceneType::GroupScene,
             lights,
@@ -540,37 +894,265 @@ impl ApiScene {
             owner: owner.clone().to_string(),
             recycle: false,
             locked: false,
-            /* Some clients (e.g. Hue Essentials) require .appdata */
-            appdata: ApiSceneAppData {
-                data: format!("xxxxx_r{room_id}"),
-                version: 1,
-            },
+            appdata: default_appdata,
             picture: String::new(),
             lastupdated: Utc::now(),
             version: ApiSceneVersion::V2 as u32,
             image: scene.metadata.image.map(|rl| rl.rid),
             group: room_id.to_string(),
-        })
+        };
+
+        if let Some(data) = res.legacy().scenes.get(uuid) {
+            api_scene.scene_type = data.scene_type;
+            if let Some(owner) = &data.owner {
+                api_scene.owner.clone_from(owner);
+            }
+            api_scene.recycle = data.recycle;
+            api_scene.locked = data.locked;
+            if let Some(appdata) = &data.appdata {
+                api_scene.appdata = appdata.clone();
+            }
+            api_scene.picture.clone_from(&data.picture);
+            api_scene.lastupdated = data.lastupdated;
+        }
+
+        Ok(api_scene)
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ApiSchedule {}
+pub struct ApiSceneNew {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub scene_type: Option<ApiSceneType>,
+    #[serde(default)]
+    pub lights: Vec<String>,
+    pub group: Option<String>,
+    #[serde(default)]
+    pub recycle: bool,
+    pub appdata: Option<ApiSceneAppData>,
+    #[serde(default)]
+    pub picture: String,
+    #[serde(default)]
+    pub lightstates: HashMap<String, ApiLightStateUpdate>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiSceneUpdate {
+    pub name: Option<String>,
+    pub lights: Option<Vec<String>>,
+    pub storelightstate: Option<bool>,
+    pub recycle: Option<bool>,
+    pub appdata: Option<ApiSceneAppData>,
+    pub picture: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiSchedule {
+    pub name: String,
+    pub description: String,
+    pub command: ApiCommand,
+    pub localtime: String,
+    #[serde(with = "date_format::utc")]
+    pub created: DateTime<Utc>,
+    pub status: ApiStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autodelete: Option<bool>,
+    /// Local time the current timer run started (timers only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starttime: Option<NaiveDateTime>,
+    pub recycle: bool,
+}
+
+impl ApiSchedule {
+    pub fn pattern(&self) -> ApiResult<TimePattern> {
+        self.localtime.parse()
+    }
+
+    /// (Re)start the schedule, which makes timers count from `now`
+    pub fn start(&mut self, now: NaiveDateTime) -> ApiResult<()> {
+        self.starttime = self
+            .pattern()?
+            .is_timer()
+            .then(|| now.with_nanosecond(0).unwrap_or(now));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiScheduleNew {
+    #[serde(default = "ApiScheduleNew::default_name")]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub command: ApiCommand,
+    pub localtime: String,
+    #[serde(default)]
+    pub status: ApiStatus,
+    pub autodelete: Option<bool>,
+    #[serde(default)]
+    pub recycle: bool,
+}
+
+impl ApiScheduleNew {
+    fn default_name() -> String {
+        "schedule".to_string()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiScheduleUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub command: Option<ApiCommand>,
+    pub localtime: Option<String>,
+    pub status: Option<ApiStatus>,
+    pub autodelete: Option<bool>,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ApiSensor {}
+pub struct ApiSensor {
+    #[serde(rename = "type")]
+    sensor_type: String,
+    name: String,
+    modelid: String,
+    manufacturername: String,
+    productname: String,
+    swversion: String,
+    uniqueid: String,
+    state: Value,
+    config: Value,
+    capabilities: Value,
+}
+
+impl ApiSensor {
+    /* thresholds (on the hue light level scale) used for "dark" and "daylight" */
+    const THOLD_DARK: u32 = 16000;
+    const THOLD_OFFSET: u32 = 7000;
+
+    fn last_updated(changed: Option<DateTime<Utc>>) -> String {
+        changed.map_or_else(
+            || String::from("none"),
+            |dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        )
+    }
+
+    fn new(uuid: &Uuid, dev: &api::Device, sensor_type: &str, state: Value, config: Value) -> Self {
+        let product_data = dev.product_data.clone();
+
+        Self {
+            sensor_type: sensor_type.to_string(),
+            name: dev.metadata.name.clone(),
+            modelid: product_data.product_name.clone(),
+            manufacturername: product_data.manufacturer_name,
+            productname: product_data.product_name,
+            swversion: product_data.software_version,
+            uniqueid: uuid.as_simple().to_string(),
+            state,
+            config,
+            capabilities: json!({
+                "certified": true,
+                "primary": sensor_type == "ZLLPresence",
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn from_dev_and_motion(uuid: &Uuid, dev: &api::Device, motion: &api::Motion) -> Self {
+        let changed = motion.motion.motion_report.as_ref().map(|rep| rep.changed);
+
+        let state = json!({
+            "presence": motion.motion.motion,
+            "lastupdated": Self::last_updated(changed),
+        });
+
+        let config = json!({
+            "on": motion.enabled,
+            "reachable": true,
+            "alert": "none",
+            "sensitivity": 2,
+            "sensitivitymax": 2,
+            "ledindication": false,
+            "usertest": false,
+            "pending": [],
+        });
+
+        Self::new(uuid, dev, "ZLLPresence", state, config)
+    }
+
+    #[must_use]
+    pub fn from_dev_and_light_level(
+        uuid: &Uuid,
+        dev: &api::Device,
+        light_level: &api::LightLevel,
+    ) -> Self {
+        let level = light_level.light.light_level;
+        let changed = light_level
+            .light
+            .light_level_report
+            .as_ref()
+            .map(|rep| rep.changed);
+
+        let state = json!({
+            "lightlevel": level,
+            "dark": level < Self::THOLD_DARK,
+            "daylight": level >= Self::THOLD_DARK + Self::THOLD_OFFSET,
+            "lastupdated": Self::last_updated(changed),
+        });
+
+        let config = json!({
+            "on": light_level.enabled,
+            "reachable": true,
+            "alert": "none",
+            "tholddark": Self::THOLD_DARK,
+            "tholdoffset": Self::THOLD_OFFSET,
+            "ledindication": false,
+            "usertest": false,
+            "pending": [],
+        });
+
+        Self::new(uuid, dev, "ZLLLightLevel", state, config)
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_dev_and_temperature(
+        uuid: &Uuid,
+        dev: &api::Device,
+        temperature: &api::Temperature,
+    ) -> Self {
+        let temp = &temperature.temperature;
+        let changed = temp.temperature_report.as_ref().map(|rep| rep.changed);
+
+        /* v1 api reports temperature in 1/100 degrees celsius */
+        let state = json!({
+            "temperature": (temp.temperature * 100.0).round() as i32,
+            "lastupdated": Self::last_updated(changed),
+        });
+
+        let config = json!({
+            "on": temperature.enabled,
+            "reachable": true,
+            "alert": "none",
+            "ledindication": false,
+            "usertest": false,
+            "pending": [],
+        });
+
+        Self::new(uuid, dev, "ZLLTemperature", state, config)
+    }
+}
 
-#[allow(clippy::zero_sized_map_values)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiUserConfig {
     pub config: ApiConfig,
     pub groups: HashMap<String, ApiGroup>,
     pub lights: HashMap<String, ApiLight>,
-    pub resourcelinks: HashMap<u32, ApiResourceLink>,
-    pub rules: HashMap<u32, ApiRule>,
+    pub resourcelinks: BTreeMap<u32, ApiResourceLink>,
+    pub rules: BTreeMap<u32, ApiRule>,
     pub scenes: HashMap<String, ApiScene>,
-    pub schedules: HashMap<u32, ApiSchedule>,
-    pub sensors: HashMap<u32, ApiSensor>,
+    pub schedules: BTreeMap<u32, ApiSchedule>,
+    pub sensors: HashMap<String, ApiSensor>,
 }
 
 impl Default for ApiConfig {
@@ -3,6 +3,8 @@ pub mod date_format;
 pub mod event;
 pub mod legacy_api;
 pub mod scene_icons;
+pub mod stream;
+pub mod timepattern;
 
 pub const HUE_BRIDGE_V2_MODEL_ID: &str = "BSB002";
 
@@ -0,0 +1,114 @@
+//! Decoding of `HueStream` packets, as sent by entertainment clients (e.g. Hue
+//! Sync) over the DTLS connection.
+//!
+//! Version 1 addresses lights by their v1 api id, version 2 addresses the
+//! channels of an entertainment configuration.
+
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum HueStreamColorMode {
+    Rgb,
+    Xy,
+}
+
+/// Target of a single color value in a packet
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum HueStreamTarget {
+    /// v1: a light, by its v1 api id
+    Light(u16),
+    /// v2: a channel of the entertainment configuration
+    Channel(u8),
+}
+
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub struct HueStreamLight {
+    pub target: HueStreamTarget,
+    /// Three 16-bit components: either (r, g, b) or (x, y, brightness)
+    pub color: [u16; 3],
+}
+
+#[derive(Debug, Clone)]
+pub struct HueStreamPacket {
+    pub version: (u8, u8),
+    pub sequence: u8,
+    pub color_mode: HueStreamColorMode,
+    /// Entertainment configuration (v2 only)
+    pub config: Option<Uuid>,
+    pub lights: Vec<HueStreamLight>,
+}
+
+impl HueStreamPacket {
+    const MAGIC: &'static [u8] = b"HueStream";
+    const HEADER_SIZE: usize = 16;
+    const CONFIG_ID_SIZE: usize = 36;
+    const V1_LIGHT_SIZE: usize = 9;
+    const V2_CHANNEL_SIZE: usize = 7;
+
+    fn color(data: &[u8]) -> [u16; 3] {
+        [
+            u16::from_be_bytes([data[0], data[1]]),
+            u16::from_be_bytes([data[2], data[3]]),
+            u16::from_be_bytes([data[4], data[5]]),
+        ]
+    }
+
+    pub fn parse(data: &[u8]) -> ApiResult<Self> {
+        if data.len() < Self::HEADER_SIZE || !data.starts_with(Self::MAGIC) {
+            return Err(ApiError::HueStreamInvalid("bad header"));
+        }
+
+        let version = (data[9], data[10]);
+        let sequence = data[11];
+        let color_mode = match data[14] {
+            0 => HueStreamColorMode::Rgb,
+            1 => HueStreamColorMode::Xy,
+            _ => return Err(ApiError::HueStreamInvalid("unknown color mode")),
+        };
+
+        let body = &data[Self::HEADER_SIZE..];
+
+        let (config, lights) = match version.0 {
+            1 => {
+                let lights = body
+                    .chunks_exact(Self::V1_LIGHT_SIZE)
+                    .filter(|chunk| chunk[0] == 0x00)
+                    .map(|chunk| HueStreamLight {
+                        target: HueStreamTarget::Light(u16::from_be_bytes([chunk[1], chunk[2]])),
+                        color: Self::color(&chunk[3..]),
+                    })
+                    .collect();
+                (None, lights)
+            }
+            2 => {
+                if body.len() < Self::CONFIG_ID_SIZE {
+                    return Err(ApiError::HueStreamInvalid("missing configuration id"));
+                }
+                let (id, channels) = body.split_at(Self::CONFIG_ID_SIZE);
+                let config = std::str::from_utf8(id)
+                    .ok()
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or(ApiError::HueStreamInvalid("bad configuration id"))?;
+                let lights = channels
+                    .chunks_exact(Self::V2_CHANNEL_SIZE)
+                    .map(|chunk| HueStreamLight {
+                        target: HueStreamTarget::Channel(chunk[0]),
+                        color: Self::color(&chunk[1..]),
+                    })
+                    .collect();
+                (Some(config), lights)
+            }
+            _ => return Err(ApiError::HueStreamInvalid("unsupported version")),
+        };
+
+        Ok(Self {
+            version,
+            sequence,
+            color_mode,
+            config,
+            lights,
+        })
+    }
+}
@@ -0,0 +1,232 @@
+//! Hue v1 time patterns, as used in schedules (`localtime`) and in rule
+//! conditions on `/config/localtime`.
+//!
+//! All times are local. Randomized times ("...A00:30:00") are accepted, but
+//! the random part is ignored.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+
+use crate::error::{ApiError, ApiResult};
+
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+/// Bitmask of weekdays, as used in "W" patterns (Monday = 64, Sunday = 1)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    pub const ALL: Self = Self(0b0111_1111);
+
+    #[must_use]
+    pub const fn contains(self, day: chrono::Weekday) -> bool {
+        self.0 & (1 << (6 - day.num_days_from_monday())) != 0
+    }
+}
+
+impl FromStr for Weekdays {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> ApiResult<Self> {
+        let bits = s
+            .parse()
+            .map_err(|_| ApiError::V1InvalidTimePattern(s.to_string()))?;
+        if bits == 0 || bits > Self::ALL.0 {
+            return Err(ApiError::V1InvalidTimePattern(s.to_string()));
+        }
+        Ok(Self(bits))
+    }
+}
+
+fn parse_time(s: &str) -> ApiResult<NaiveTime> {
+    NaiveTime::parse_from_str(s, TIME_FORMAT)
+        .map_err(|_| ApiError::V1InvalidTimePattern(s.to_string()))
+}
+
+fn parse_duration(s: &str) -> ApiResult<Duration> {
+    let time = parse_time(s)?;
+    Ok(time - NaiveTime::MIN)
+}
+
+/// Parse a "PThh:mm:ss" duration, as used by timers and "stable" rule
+/// conditions
+pub fn parse_period(s: &str) -> ApiResult<Duration> {
+    let period = s
+        .strip_prefix("PT")
+        .ok_or_else(|| ApiError::V1InvalidTimePattern(s.to_string()))?;
+    parse_duration(strip_random(period))
+}
+
+/// Remove the randomization suffix ("A00:30:00") from a pattern
+fn strip_random(s: &str) -> &str {
+    s.split_once('A').map_or(s, |(pattern, _)| pattern)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimePattern {
+    /// Once, at a fixed time ("2024-06-01T07:00:00")
+    Absolute(NaiveDateTime),
+
+    /// On selected weekdays, at a fixed time ("W124/T07:00:00")
+    Recurring { weekdays: Weekdays, time: NaiveTime },
+
+    /// Counting from the start time of the schedule: once ("PT00:10:00"),
+    /// a number of times ("R05/PT00:10:00") or forever ("R/PT00:10:00")
+    Timer {
+        period: Duration,
+        repeat: Option<u32>,
+    },
+}
+
+impl FromStr for TimePattern {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> ApiResult<Self> {
+        let invalid = || ApiError::V1InvalidTimePattern(s.to_string());
+        let pattern = strip_random(s);
+
+        if let Some(rest) = pattern.strip_prefix('W') {
+            let (days, time) = rest.split_once("/T").ok_or_else(invalid)?;
+            return Ok(Self::Recurring {
+                weekdays: days.parse()?,
+                time: parse_time(time)?,
+            });
+        }
+
+        if let Some(rest) = pattern.strip_prefix('R') {
+            let (count, period) = rest.split_once('/').ok_or_else(invalid)?;
+            let repeat = match count {
+                "" => None,
+                count => Some(count.parse().map_err(|_| invalid())?),
+            };
+            return Ok(Self::Timer {
+                period: parse_period(period)?,
+                repeat,
+            });
+        }
+
+        if pattern.starts_with("PT") {
+            return Ok(Self::Timer {
+                period: parse_period(pattern)?,
+                repeat: Some(1),
+            });
+        }
+
+        NaiveDateTime::parse_from_str(pattern, DATETIME_FORMAT)
+            .map(Self::Absolute)
+            .map_err(|_| invalid())
+    }
+}
+
+impl TimePattern {
+    #[must_use]
+    pub const fn is_timer(&self) -> bool {
+        matches!(self, Self::Timer { .. })
+    }
+
+    /// The latest occurrence at or before `now`, for timers counting from
+    /// `start`
+    fn last_occurrence(
+        &self,
+        start: Option<NaiveDateTime>,
+        now: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        match *self {
+            Self::Absolute(time) => (time <= now).then_some(time),
+            Self::Recurring { weekdays, time } => (0..7)
+                .filter_map(|days| now.date().checked_sub_signed(Duration::days(days)))
+                .filter(|date| weekdays.contains(date.weekday()))
+                .map(|date| date.and_time(time))
+                .find(|occurrence| *occurrence <= now),
+            Self::Timer { period, repeat } => {
+                let start = start?;
+                let elapsed = (now - start).num_seconds();
+                let runs = elapsed.checked_div(period.num_seconds())?;
+                let runs = repeat.map_or(runs, |max| runs.min(i64::from(max)));
+                let runs = i32::try_from(runs).ok()?;
+                (runs > 0).then(|| start + period * runs)
+            }
+        }
+    }
+
+    /// True if the pattern has an occurrence in the interval (`from`, `to`]
+    #[must_use]
+    pub fn fires(
+        &self,
+        start: Option<NaiveDateTime>,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> bool {
+        self.last_occurrence(start, to)
+            .is_some_and(|occurrence| occurrence > from)
+    }
+
+    /// True if the pattern has no occurrences left after `now`
+    #[must_use]
+    pub fn is_finished(&self, start: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+        match *self {
+            Self::Absolute(time) => time <= now,
+            Self::Recurring { .. } => false,
+            Self::Timer { period, repeat } => match (start, repeat) {
+                (Some(start), Some(max)) => {
+                    i32::try_from(max).is_ok_and(|max| start + period * max <= now)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A daily time interval ("T08:00:00/T10:00:00"), optionally limited to
+/// certain weekdays ("W124/T08:00:00/T10:00:00")
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimeInterval {
+    weekdays: Weekdays,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl FromStr for TimeInterval {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> ApiResult<Self> {
+        let invalid = || ApiError::V1InvalidTimePattern(s.to_string());
+
+        let (weekdays, interval) = match s.strip_prefix('W') {
+            Some(rest) => {
+                let (days, interval) = rest.split_once('/').ok_or_else(invalid)?;
+                (days.parse()?, interval)
+            }
+            None => (Weekdays::ALL, s),
+        };
+
+        let (start, end) = interval.split_once('/').ok_or_else(invalid)?;
+        let start = start.strip_prefix('T').ok_or_else(invalid)?;
+        let end = end.strip_prefix('T').ok_or_else(invalid)?;
+
+        Ok(Self {
+            weekdays,
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+}
+
+impl TimeInterval {
+    /// True if `now` is inside the interval. Intervals may span midnight, in
+    /// which case the weekday applies to the start of the interval.
+    #[must_use]
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+
+        if self.start <= self.end {
+            self.weekdays.contains(now.weekday()) && self.start <= time && time < self.end
+        } else if time >= self.start {
+            self.weekdays.contains(now.weekday())
+        } else {
+            time < self.end && self.weekdays.contains(now.weekday().pred())
+        }
+    }
+}
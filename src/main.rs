@@ -1,13 +1,41 @@
 use std::io::Write;
+use std::net::SocketAddr;
 
+use clap::{Parser, Subcommand};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tokio::task::JoinSet;
 
-use bifrost::config;
-use bifrost::error::ApiResult;
+use bifrost::config::{self, AppConfig};
+use bifrost::error::{ApiError, ApiResult};
 use bifrost::mdns;
+use bifrost::routes::auth::HUE_APPLICATION_KEY;
 use bifrost::server::{self, appstate::AppState, banner};
 use bifrost::z2m;
 
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the bridge emulator (default)
+    Run,
+
+    /// Press the link button on a running bifrost instance, to allow pairing new apps
+    Linkbutton {
+        /// Number of seconds to keep the link button pressed [default: from config]
+        seconds: Option<u64>,
+
+        /// Application key of an already paired app [not needed with `allow_local`]
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
 /*
  * Formatter function to output in syslog format. This makes sense when running
  * as a service (where output might go to a log file, or the system journal)
@@ -80,6 +108,19 @@ async fn build_tasks(appstate: AppState) -> ApiResult<JoinSet<ApiResult<()>>> {
         svc,
         tls_config,
     ));
+    tasks.spawn(server::entertainment::entertainment_server(
+        appstate.res.clone(),
+        bconf.ipaddress,
+        bconf.entertainment_port,
+    ));
+    tasks.spawn(server::scheduler::smart_scene_scheduler(
+        appstate.res.clone(),
+        bconf.location,
+    ));
+    tasks.spawn(server::automation::behavior_engine(appstate.res.clone()));
+    tasks.spawn(server::legacy_automation::legacy_engine(
+        appstate.res.clone(),
+    ));
     tasks.spawn(server::config_writer(appstate.res.clone(), state_file));
 
     for (name, server) in &appstate.config().z2m.servers {
@@ -95,9 +136,57 @@ async fn build_tasks(appstate: AppState) -> ApiResult<JoinSet<ApiResult<()>>> {
     Ok(tasks)
 }
 
+async fn press_link_button(
+    config: &AppConfig,
+    seconds: Option<u64>,
+    key: Option<&str>,
+) -> ApiResult<()> {
+    let addr = SocketAddr::from((config.bridge.ipaddress, config.bridge.http_port));
+
+    let path = seconds.map_or_else(
+        || String::from("/bifrost/linkbutton"),
+        |secs| format!("/bifrost/linkbutton?seconds={secs}"),
+    );
+
+    let auth = key.map_or_else(String::new, |key| {
+        format!("{HUE_APPLICATION_KEY}: {key}\r\n")
+    });
+
+    log::info!("Pressing link button on bifrost at {addr}..");
+
+    let mut stream = TcpStream::connect(addr).await?;
+    stream
+        .write_all(
+            format!(
+                "POST {path} HTTP/1.1\r\nHost: {addr}\r\n{auth}Content-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await?;
+
+    let status = reply.lines().next().unwrap_or_default();
+    if status.split(' ').nth(1) != Some("200") {
+        return Err(ApiError::LinkButtonFailed(status.to_string()));
+    }
+
+    log::info!("Link button pressed");
+
+    Ok(())
+}
+
 async fn run() -> ApiResult<()> {
     init_logging()?;
 
+    let cli = Cli::parse();
+
+    if let Some(Command::Linkbutton { seconds, key }) = cli.command {
+        let config = config::parse("config.yaml".into())?;
+        return press_link_button(&config, seconds, key.as_deref()).await;
+    }
+
     #[cfg(feature = "server-banner")]
     banner::print()?;
 
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::types::XY;
+
+/// Hue (in degrees, `0.0..360.0`) and saturation (`0.0..=1.0`)
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HS {
+    pub hue: f64,
+    pub sat: f64,
+}
+
+impl HS {
+    #[must_use]
+    pub const fn new(hue: f64, sat: f64) -> Self {
+        Self { hue, sat }
+    }
+
+    /// Convert to (gamma-compressed) sRGB components at full value
+    #[must_use]
+    pub fn to_rgb(self) -> [f64; 3] {
+        let hue = self.hue.rem_euclid(360.0) / 60.0;
+        let sat = self.sat.clamp(0.0, 1.0);
+
+        let chroma = sat;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let min = 1.0 - chroma;
+
+        /* truncation is intended: hue is in 0.0..6.0 */
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let [r, g, b] = match hue as u8 {
+            0 => [chroma, x, 0.0],
+            1 => [x, chroma, 0.0],
+            2 => [0.0, chroma, x],
+            3 => [0.0, x, chroma],
+            4 => [x, 0.0, chroma],
+            _ => [chroma, 0.0, x],
+        };
+
+        [r + min, g + min, b + min]
+    }
+
+    /// Convert from (gamma-compressed) sRGB components, ignoring value
+    #[must_use]
+    pub fn from_rgb([r, g, b]: [f64; 3]) -> Self {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        if max <= 0.0 || delta <= 0.0 {
+            return Self::new(0.0, 0.0);
+        }
+
+        let hue = if (max - r).abs() < f64::EPSILON {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if (max - g).abs() < f64::EPSILON {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        Self::new(hue, delta / max)
+    }
+}
+
+impl XY {
+    /// Convert to (gamma-compressed) sRGB components, scaled so the
+    /// brightest component is `1.0`. Colors outside the wide gamut are
+    /// clipped to the nearest reproducible color.
+    #[must_use]
+    pub fn to_rgb(self) -> [f64; 3] {
+        fn gamma(c: f64) -> f64 {
+            if c <= 0.003_130_8 {
+                12.92 * c
+            } else {
+                1.055f64.mul_add(c.powf(1.0 / 2.4), -0.055)
+            }
+        }
+
+        if self.y <= 0.0 {
+            return [1.0, 1.0, 1.0];
+        }
+
+        /* CIE XYZ, at luminance 1.0 */
+        let cie_x = self.x / self.y;
+        let cie_z = (1.0 - self.x - self.y) / self.y;
+
+        /* inverse of the wide gamut conversion in [`XY::from_rgb`] */
+        let red = cie_x.mul_add(1.656_492, cie_z.mul_add(-0.255_038, -0.354_851));
+        let green = cie_x.mul_add(-0.707_196, cie_z.mul_add(0.036_152, 1.655_397));
+        let blue = cie_x.mul_add(0.051_713, cie_z.mul_add(1.011_530, -0.121_364));
+
+        let (red, green, blue) = (red.max(0.0), green.max(0.0), blue.max(0.0));
+        let max = red.max(green).max(blue);
+        if max <= 0.0 {
+            return [1.0, 1.0, 1.0];
+        }
+
+        [gamma(red / max), gamma(green / max), gamma(blue / max)]
+    }
+
+    #[must_use]
+    pub fn from_hs(hs: HS) -> Self {
+        let [r, g, b] = hs.to_rgb();
+        Self::from_rgb(r, g, b).0
+    }
+
+    #[must_use]
+    pub fn to_hs(self) -> HS {
+        HS::from_rgb(self.to_rgb())
+    }
+
+    /// Point on the planckian locus for a color temperature in mirek
+    /// (cubic spline approximation by Kim et al.)
+    #[must_use]
+    pub fn from_mirek(mirek: u32) -> Self {
+        let kelvin = (1_000_000.0 / f64::from(mirek.max(1))).clamp(1667.0, 25000.0);
+        let t1 = 1000.0 / kelvin;
+        let t2 = t1 * t1;
+        let t3 = t2 * t1;
+
+        let x = if kelvin <= 4000.0 {
+            (-0.266_123_9f64).mul_add(
+                t3,
+                (-0.234_358_9f64).mul_add(t2, 0.877_695_6f64.mul_add(t1, 0.179_910)),
+            )
+        } else {
+            (-3.025_846_9f64).mul_add(
+                t3,
+                2.107_037_9f64.mul_add(t2, 0.222_634_7f64.mul_add(t1, 0.240_390)),
+            )
+        };
+
+        let x2 = x * x;
+        let x3 = x2 * x;
+
+        let y = if kelvin <= 2222.0 {
+            (-1.106_381_4f64).mul_add(
+                x3,
+                (-1.348_110_20f64).mul_add(x2, 2.185_558_32f64.mul_add(x, -0.202_196_83)),
+            )
+        } else if kelvin <= 4000.0 {
+            (-0.954_947_6f64).mul_add(
+                x3,
+                (-1.374_185_93f64).mul_add(x2, 2.091_370_15f64.mul_add(x, -0.167_488_67)),
+            )
+        } else {
+            3.081_758_0f64.mul_add(
+                x3,
+                (-5.873_386_70f64).mul_add(x2, 3.751_129_97f64.mul_add(x, -0.370_014_83)),
+            )
+        };
+
+        Self::new(x, y)
+    }
+
+    /// Nearest color temperature in mirek, limited to `min..=max`
+    #[must_use]
+    pub fn to_mirek(self, min: u32, max: u32) -> u32 {
+        let n = (self.x - 0.3320) / (0.1858 - self.y);
+        let kelvin = 449.0f64.mul_add(
+            n.powi(3),
+            3525.0f64.mul_add(n.powi(2), 6823.3f64.mul_add(n, 5520.33)),
+        );
+
+        if kelvin <= 0.0 {
+            return max;
+        }
+
+        /* value is clamped to min..=max */
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mirek = (1_000_000.0 / kelvin)
+            .round()
+            .clamp(f64::from(min), f64::from(max)) as u32;
+
+        mirek
+    }
+}
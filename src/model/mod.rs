@@ -1,2 +1,4 @@
+pub mod color;
 pub mod state;
+pub mod sun;
 pub mod types;
@@ -1,5 +1,6 @@
 use std::{collections::BTreeMap, io::Read};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_yml::Value;
 use uuid::Uuid;
@@ -7,6 +8,7 @@ use uuid::Uuid;
 use crate::{
     error::{ApiError, ApiResult},
     hue::api::{Resource, ResourceLink},
+    hue::legacy_api::{ApiResourceLink, ApiRule, ApiSceneData, ApiSchedule},
 };
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -38,6 +40,42 @@ impl AuxData {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiUser {
+    pub client_key: Uuid,
+    pub device_type: String,
+    pub name: String,
+    pub create_date: DateTime<Utc>,
+    pub last_use_date: DateTime<Utc>,
+}
+
+impl ApiUser {
+    /// Construct a new user from a `devicetype` string, as supplied when
+    /// pairing (e.g. "Hue#iPhone" becomes device type "Hue", name "iPhone")
+    #[must_use]
+    pub fn new(spec: &str) -> Self {
+        let (device_type, name) = spec.split_once('#').unwrap_or((spec, ""));
+        let now = Utc::now();
+
+        Self {
+            client_key: Uuid::new_v4(),
+            device_type: device_type.to_string(),
+            name: name.to_string(),
+            create_date: now,
+            last_use_date: now,
+        }
+    }
+
+    #[must_use]
+    pub fn devicetype(&self) -> String {
+        if self.name.is_empty() {
+            self.device_type.clone()
+        } else {
+            format!("{}#{}", self.device_type, self.name)
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct IdMap {
     forward: BTreeMap<Uuid, u32>,
@@ -89,6 +127,29 @@ impl IdMap {
     }
 }
 
+/// Hue v1 objects, which have no v2 counterpart
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LegacyData {
+    #[serde(default)]
+    pub schedules: BTreeMap<u32, ApiSchedule>,
+    #[serde(default)]
+    pub rules: BTreeMap<u32, ApiRule>,
+    #[serde(default)]
+    pub resourcelinks: BTreeMap<u32, ApiResourceLink>,
+    #[serde(default)]
+    pub scenes: BTreeMap<Uuid, ApiSceneData>,
+}
+
+impl LegacyData {
+    /// Lowest unused id in a v1 collection (v1 ids start at 1)
+    #[must_use]
+    pub fn next_id<T>(map: &BTreeMap<u32, T>) -> u32 {
+        (1..=u32::MAX)
+            .find(|id| !map.contains_key(id))
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub enum StateVersion {
     /// Version 0: (`res`, `aux`) tuple, no version field in state
@@ -104,6 +165,10 @@ pub struct State {
     version: StateVersion,
     aux: BTreeMap<Uuid, AuxData>,
     id_v1: IdMap,
+    #[serde(default)]
+    users: BTreeMap<Uuid, ApiUser>,
+    #[serde(default)]
+    legacy: LegacyData,
     pub res: BTreeMap<Uuid, Resource>,
 }
 
@@ -156,6 +221,8 @@ impl State {
             version: StateVersion::V1,
             aux,
             id_v1,
+            users: BTreeMap::new(),
+            legacy: LegacyData::default(),
             res,
         })
     }
@@ -211,6 +278,33 @@ impl State {
         Ok(())
     }
 
+    pub fn user_add(&mut self, username: Uuid, user: ApiUser) {
+        self.users.insert(username, user);
+    }
+
+    #[must_use]
+    pub fn user_get(&self, username: &Uuid) -> Option<&ApiUser> {
+        self.users.get(username)
+    }
+
+    pub fn user_get_mut(&mut self, username: &Uuid) -> Option<&mut ApiUser> {
+        self.users.get_mut(username)
+    }
+
+    #[must_use]
+    pub const fn users(&self) -> &BTreeMap<Uuid, ApiUser> {
+        &self.users
+    }
+
+    #[must_use]
+    pub const fn legacy(&self) -> &LegacyData {
+        &self.legacy
+    }
+
+    pub fn legacy_mut(&mut self) -> &mut LegacyData {
+        &mut self.legacy
+    }
+
     #[must_use]
     pub fn id_v1(&self, uuid: &Uuid) -> Option<u32> {
         self.id_v1.id(uuid)
@@ -0,0 +1,76 @@
+//! Sunrise and sunset times, using the sunrise equation (accurate to within a
+//! minute or two, which is plenty for scheduling lights).
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Julian day of the unix epoch
+const JULIAN_UNIX_EPOCH: f64 = 2_440_587.5;
+
+/// Julian day of the J2000 epoch
+const JULIAN_2000: f64 = 2_451_545.0;
+
+/// Sunrise and sunset (in UTC) for the given date and location.
+///
+/// Returns [`None`] if the sun does not rise or set on that day (polar day
+/// or polar night).
+#[must_use]
+pub fn sunrise_sunset(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let unix_days = date.signed_duration_since(DateTime::UNIX_EPOCH.date_naive());
+    #[allow(clippy::cast_precision_loss)]
+    let julian = unix_days.num_days() as f64 + JULIAN_UNIX_EPOCH;
+
+    /* mean solar time */
+    let days = (julian - JULIAN_2000 + 0.0008).ceil() - longitude / 360.0;
+
+    /* solar mean anomaly */
+    let anomaly = 0.985_600_28f64.mul_add(days, 357.5291).rem_euclid(360.0);
+    let anomaly_rad = anomaly.to_radians();
+
+    /* equation of the center */
+    let center = 0.0003f64.mul_add(
+        (3.0 * anomaly_rad).sin(),
+        1.9148f64.mul_add(anomaly_rad.sin(), 0.02 * (2.0 * anomaly_rad).sin()),
+    );
+
+    /* ecliptic longitude */
+    let ecliptic = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let ecliptic_rad = ecliptic.to_radians();
+
+    /* solar transit */
+    let transit = 0.0053f64.mul_add(
+        anomaly_rad.sin(),
+        (-0.0069f64).mul_add((2.0 * ecliptic_rad).sin(), JULIAN_2000 + days),
+    );
+
+    /* declination of the sun */
+    let declination_sin = ecliptic_rad.sin() * 23.4397f64.to_radians().sin();
+    let declination_cos = declination_sin.asin().cos();
+
+    /* hour angle, including correction for refraction and solar disc */
+    let latitude_rad = latitude.to_radians();
+    let hour_angle_cos = latitude_rad
+        .sin()
+        .mul_add(-declination_sin, (-0.833f64).to_radians().sin())
+        / (latitude_rad.cos() * declination_cos);
+
+    if !(-1.0..=1.0).contains(&hour_angle_cos) {
+        return None;
+    }
+
+    let hour_angle = hour_angle_cos.acos().to_degrees();
+
+    let to_utc = |julian: f64| {
+        #[allow(clippy::cast_possible_truncation)]
+        let secs = ((julian - JULIAN_UNIX_EPOCH) * 86400.0).round() as i64;
+        DateTime::from_timestamp(secs, 0)
+    };
+
+    Some((
+        to_utc(transit - hour_angle / 360.0)?,
+        to_utc(transit + hour_angle / 360.0)?,
+    ))
+}
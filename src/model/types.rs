@@ -16,6 +16,33 @@ impl XY {
     pub const fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
+
+    /// Convert (gamma-compressed) sRGB components in the range `0.0..=1.0`
+    /// to CIE xy coordinates, and a brightness in the range `0.0..=1.0`
+    #[must_use]
+    pub fn from_rgb(red: f64, green: f64, blue: f64) -> (Self, f64) {
+        fn linear(c: f64) -> f64 {
+            if c > 0.04045 {
+                ((c + 0.055) / 1.055).powf(2.4)
+            } else {
+                c / 12.92
+            }
+        }
+
+        let (red, green, blue) = (linear(red), linear(green), linear(blue));
+
+        /* wide gamut conversion, D65 reference white */
+        let x = red.mul_add(0.664_511, green.mul_add(0.154_324, blue * 0.162_028));
+        let y = red.mul_add(0.283_881, green.mul_add(0.668_433, blue * 0.047_685));
+        let z = red.mul_add(0.000_088, green.mul_add(0.072_310, blue * 0.986_039));
+
+        let sum = x + y + z;
+        if sum == 0.0 {
+            return (Self::D65_WHITE_POINT, 0.0);
+        }
+
+        (Self::new(x / sum, y / sum), y.min(1.0))
+    }
 }
 
 impl From<[f64; 2]> for XY {
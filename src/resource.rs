@@ -1,7 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
 use std::sync::Arc;
 
+use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
 use tokio::sync::broadcast::{Receiver, Sender};
 use tokio::sync::Notify;
@@ -9,19 +10,42 @@ use uuid::Uuid;
 
 use crate::error::{ApiError, ApiResult};
 use crate::hue::api::{
-    Bridge, BridgeHome, Device, DeviceArchetype, DeviceProductData, Metadata, RType, Resource,
-    ResourceLink, ResourceRecord, TimeZone, ZigbeeConnectivity, ZigbeeConnectivityStatus,
-    ZigbeeDeviceDiscovery,
+    BehaviorInstanceUpdate, BridgeHomeUpdate, BridgeUpdate, ButtonUpdate, DeviceMetadataUpdate,
+    DeviceUpdate, EntertainmentConfigurationUpdate, GeofenceClientUpdate, GroupedLightUpdate,
+    LightLevelUpdate, LightUpdate, MotionUpdate, RoomMetadataUpdate, RoomUpdate, SceneUpdate,
+    SmartSceneUpdate, TemperatureUpdate, Update, ZigbeeConnectivityUpdate,
+    ZigbeeDeviceDiscoveryUpdate, ZoneUpdate,
+};
+use crate::hue::api::{
+    BehaviorScriptType, Bridge, BridgeHome, Device, DeviceArchetype, DeviceProductData,
+    DimmingUpdate, EntertainmentConfiguration, EntertainmentConfigurationStatus, GroupedLight,
+    Light, LightMode, Metadata, On, RType, Resource, ResourceLink, ResourceRecord, Room, Scene,
+    SceneStatus, TimeZone, ZigbeeConnectivity, ZigbeeConnectivityStatus, ZigbeeDeviceDiscovery,
+    ZigbeeDeviceDiscoveryStatus, Zone,
 };
-use crate::hue::api::{GroupedLightUpdate, LightUpdate, SceneUpdate, Update};
 use crate::hue::event::EventBlock;
-use crate::model::state::{AuxData, State};
+use crate::model::state::{ApiUser, AuxData, LegacyData, State};
+use crate::z2m;
 use crate::z2m::request::ClientRequest;
 
+/// Members of each grouped light, and the grouped lights of each light
+#[derive(Clone, Debug, Default)]
+struct GroupIndex {
+    members: HashMap<Uuid, Vec<ResourceLink>>,
+    parents: HashMap<Uuid, Vec<Uuid>>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Resources {
     state: State,
     state_updates: Arc<Notify>,
+    link_button: Option<DateTime<Utc>>,
+    /// End of the current search for new zigbee devices
+    device_search: Option<DateTime<Utc>>,
+    /// Name of the z2m server whose network is reported as the bridge network
+    network_server: Option<String>,
+    /// Built on demand, and dropped whenever group membership might change
+    group_index: Option<GroupIndex>,
     pub hue_updates: Sender<EventBlock>,
     pub z2m_updates: Sender<Arc<ClientRequest>>,
 }
@@ -35,6 +59,10 @@ impl Resources {
         Self {
             state,
             state_updates: Arc::new(Notify::new()),
+            link_button: None,
+            device_search: None,
+            network_server: None,
+            group_index: None,
             hue_updates: Sender::new(32),
             z2m_updates: Sender::new(32),
         }
@@ -42,6 +70,7 @@ impl Resources {
 
     pub fn read(&mut self, rdr: impl Read) -> ApiResult<()> {
         self.state = State::from_reader(rdr)?;
+        self.group_index = None;
         Ok(())
     }
 
@@ -57,6 +86,15 @@ impl Resources {
         self.add_bridge(bridge_id.to_owned())
     }
 
+    /// Advertise the behavior scripts that bifrost can execute
+    pub fn add_behavior_scripts(&mut self) -> ApiResult<()> {
+        for script in BehaviorScriptType::ALL {
+            let link = RType::BehaviorScript.link_to(script.id());
+            self.add(&link, Resource::BehaviorScript(script.script()))?;
+        }
+        Ok(())
+    }
+
     pub fn aux_get(&self, link: &ResourceLink) -> ApiResult<&AuxData> {
         self.state.aux_get(link)
     }
@@ -65,6 +103,67 @@ impl Resources {
         self.state.aux_set(link, aux);
     }
 
+    pub fn add_user(&mut self, username: Uuid, user: ApiUser) {
+        log::info!("Registered new user {username} ({})", user.devicetype());
+        self.state.user_add(username, user);
+        self.state_updates.notify_one();
+    }
+
+    #[must_use]
+    pub fn get_user(&self, username: &Uuid) -> Option<&ApiUser> {
+        self.state.user_get(username)
+    }
+
+    #[must_use]
+    pub fn get_users(&self) -> Vec<(&Uuid, &ApiUser)> {
+        self.state.users().iter().collect()
+    }
+
+    /// Mark user as seen, returning false if the user is not known
+    pub fn touch_user(&mut self, username: &Uuid) -> bool {
+        /* avoid rewriting the state file on every single request */
+        const RESOLUTION: Duration = Duration::minutes(1);
+
+        let Some(user) = self.state.user_get_mut(username) else {
+            return false;
+        };
+
+        let now = Utc::now();
+        if now - user.last_use_date > RESOLUTION {
+            user.last_use_date = now;
+            self.state_updates.notify_one();
+        }
+
+        true
+    }
+
+    #[must_use]
+    pub const fn legacy(&self) -> &LegacyData {
+        self.state.legacy()
+    }
+
+    /// Mutable access to the v1 schedules, rules and resourcelinks. Assumes
+    /// the caller will make changes, so the state file gets saved.
+    pub fn legacy_mut(&mut self) -> &mut LegacyData {
+        self.state_updates.notify_one();
+        self.state.legacy_mut()
+    }
+
+    pub fn press_link_button(&mut self, duration: Duration) {
+        log::info!(
+            "Link button pressed, pairing enabled for {}s",
+            duration.num_seconds()
+        );
+        self.link_button = Some(Utc::now() + duration);
+    }
+
+    #[must_use]
+    pub fn link_button_pressed(&self) -> bool {
+        self.link_button
+            .is_some_and(|deadline| Utc::now() < deadline)
+    }
+
+    #[allow(clippy::too_many_lines)]
     fn generate_update(obj: &Resource) -> ApiResult<Option<Update>> {
         match obj {
             Resource::Light(light) => {
@@ -90,7 +189,91 @@ impl Resources {
 
                 Ok(Some(Update::Scene(upd)))
             }
-            Resource::Room(_) => Ok(None),
+            Resource::Button(button) => Ok(Some(Update::Button(ButtonUpdate {
+                button: Some(button.button.clone()),
+            }))),
+            Resource::Motion(motion) => Ok(Some(Update::Motion(MotionUpdate {
+                enabled: Some(motion.enabled),
+                motion: Some(motion.motion.clone()),
+            }))),
+            Resource::LightLevel(light_level) => Ok(Some(Update::LightLevel(LightLevelUpdate {
+                enabled: Some(light_level.enabled),
+                light: Some(light_level.light.clone()),
+            }))),
+            Resource::Temperature(temp) => Ok(Some(Update::Temperature(TemperatureUpdate {
+                enabled: Some(temp.enabled),
+                temperature: Some(temp.temperature.clone()),
+            }))),
+            Resource::ZigbeeConnectivity(zbc) => {
+                Ok(Some(Update::ZigbeeConnectivity(ZigbeeConnectivityUpdate {
+                    channel: zbc.channel.clone(),
+                    extended_pan_id: zbc.extended_pan_id.clone(),
+                    mac_address: Some(zbc.mac_address.clone()),
+                    status: Some(zbc.status),
+                })))
+            }
+            Resource::ZigbeeDeviceDiscovery(zbdd) => Ok(Some(Update::ZigbeeDeviceDiscovery(
+                ZigbeeDeviceDiscoveryUpdate {
+                    action: None,
+                    status: Some(zbdd.status),
+                },
+            ))),
+            Resource::Zone(zone) => Ok(Some(Update::Zone(ZoneUpdate {
+                metadata: Some(RoomMetadataUpdate {
+                    name: Some(zone.metadata.name.clone()),
+                    archetype: Some(zone.metadata.archetype),
+                }),
+                children: Some(zone.children.clone()),
+            }))),
+            Resource::Room(room) => Ok(Some(Update::Room(RoomUpdate {
+                metadata: Some(RoomMetadataUpdate {
+                    name: Some(room.metadata.name.clone()),
+                    archetype: Some(room.metadata.archetype),
+                }),
+                children: Some(room.children.clone()),
+            }))),
+            Resource::Device(dev) => Ok(Some(Update::Device(DeviceUpdate {
+                metadata: Some(DeviceMetadataUpdate::from(&dev.metadata)),
+                services: Some(dev.services.clone()),
+                identify: None,
+            }))),
+            Resource::Bridge(bridge) => Ok(Some(Update::Bridge(BridgeUpdate {
+                time_zone: Some(bridge.time_zone.clone()),
+            }))),
+            Resource::BridgeHome(home) => Ok(Some(Update::BridgeHome(BridgeHomeUpdate {
+                children: Some(home.children.clone()),
+                services: Some(home.services.clone()),
+            }))),
+            Resource::EntertainmentConfiguration(ent) => Ok(Some(
+                Update::EntertainmentConfiguration(EntertainmentConfigurationUpdate {
+                    metadata: Some(ent.metadata.clone()),
+                    configuration_type: Some(ent.configuration_type),
+                    status: Some(ent.status),
+                    locations: Some(ent.locations.clone()),
+                    channels: Some(ent.channels.clone()),
+                    ..Default::default()
+                }),
+            )),
+            Resource::SmartScene(scene) => Ok(Some(Update::SmartScene(SmartSceneUpdate {
+                state: Some(scene.state),
+                active_timeslot: scene.active_timeslot,
+                ..Default::default()
+            }))),
+            Resource::BehaviorInstance(inst) => {
+                Ok(Some(Update::BehaviorInstance(BehaviorInstanceUpdate {
+                    enabled: Some(inst.enabled),
+                    configuration: Some(inst.configuration.clone()),
+                    metadata: Some(inst.metadata.clone()),
+                    state: Some(inst.state.clone()),
+                    status: Some(inst.status),
+                })))
+            }
+            Resource::GeofenceClient(client) => {
+                Ok(Some(Update::GeofenceClient(GeofenceClientUpdate {
+                    name: Some(client.name.clone()),
+                    is_at_home: client.is_at_home,
+                })))
+            }
             obj => Err(ApiError::UpdateUnsupported(obj.rtype())),
         }
     }
@@ -105,12 +288,22 @@ impl Resources {
     {
         let obj = self.state.get_mut(id)?;
         func(obj.try_into()?)?;
+        let rtype = obj.rtype();
 
         if let Some(delta) = Self::generate_update(obj)? {
             let id_v1 = self.state.id_v1(id);
             self.hue_event(EventBlock::update(id, id_v1, delta)?);
         }
 
+        /* grouped lights follow the state of their member lights */
+        if rtype == RType::Light {
+            self.update_grouped_lights_for(id)?;
+        }
+
+        if matches!(rtype, RType::Device | RType::Room | RType::Zone) {
+            self.group_index = None;
+        }
+
         self.state_updates.notify_one();
 
         Ok(())
@@ -126,6 +319,218 @@ impl Resources {
         })
     }
 
+    /// The lights in a room, zone or bridge home
+    pub fn lights_of_group(&self, group: &ResourceLink) -> ApiResult<Vec<ResourceLink>> {
+        let lights = match group.rtype {
+            RType::Room => self
+                .get::<Room>(group)?
+                .children
+                .iter()
+                .filter_map(|dev| self.get::<Device>(dev).ok())
+                .flat_map(|dev| dev.services.iter())
+                .filter(|svc| svc.rtype == RType::Light)
+                .copied()
+                .collect(),
+            RType::Zone => self
+                .get::<Zone>(group)?
+                .children
+                .iter()
+                .filter(|rl| rl.rtype == RType::Light)
+                .copied()
+                .collect(),
+            RType::BridgeHome => self
+                .state
+                .res
+                .iter()
+                .filter(|(_, obj)| obj.rtype() == RType::Light)
+                .map(|(id, _)| RType::Light.link_to(*id))
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok(lights)
+    }
+
+    /// Recalculate the grouped lights that `light` is a member of. Like on a
+    /// real bridge, a group is on if any light is on, and its brightness is
+    /// the average of the lights that are on.
+    #[allow(clippy::cast_precision_loss)]
+    fn update_grouped_lights_for(&mut self, light: &Uuid) -> ApiResult<()> {
+        let index = self.group_index();
+        let groups: Vec<(Uuid, Vec<ResourceLink>)> = index
+            .parents
+            .get(light)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| Some((*id, index.members.get(id)?.clone())))
+            .collect();
+
+        for (id, lights) in groups {
+            let lights: Vec<&Light> = lights
+                .iter()
+                .filter_map(|rl| self.get::<Light>(rl).ok())
+                .collect();
+
+            let on = lights.iter().any(|light| light.on.on);
+            let levels: Vec<f64> = lights
+                .iter()
+                .filter(|light| light.on.on)
+                .filter_map(|light| Some(light.dimming?.brightness))
+                .collect();
+            let brightness =
+                (!levels.is_empty()).then(|| levels.iter().sum::<f64>() / levels.len() as f64);
+
+            let glight = self.get::<GroupedLight>(&RType::GroupedLight.link_to(id))?;
+            if glight.on.map(|on| on.on) == Some(on)
+                && (brightness.is_none() || glight.as_brightness_opt() == brightness)
+            {
+                continue;
+            }
+
+            self.update::<GroupedLight>(&id, |glight| {
+                glight.on = Some(On::new(on));
+                if let Some(brightness) = brightness {
+                    glight.dimming = Some(DimmingUpdate::new(brightness));
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Membership of all grouped lights, built from the rooms, zones and
+    /// bridge home that own them
+    fn group_index(&mut self) -> &GroupIndex {
+        if self.group_index.is_none() {
+            let mut index = GroupIndex::default();
+
+            for (id, obj) in &self.state.res {
+                let Resource::GroupedLight(glight) = obj else {
+                    continue;
+                };
+                let Ok(lights) = self.lights_of_group(&glight.owner) else {
+                    continue;
+                };
+
+                for light in &lights {
+                    index.parents.entry(light.rid).or_default().push(*id);
+                }
+                index.members.insert(*id, lights);
+            }
+
+            self.group_index = Some(index);
+        }
+
+        self.group_index.get_or_insert_with(GroupIndex::default)
+    }
+
+    /// Find the resource of the given type, whose aux data topic matches
+    #[must_use]
+    pub fn find_by_topic(&self, rtype: RType, topic: &str) -> Option<ResourceLink> {
+        self.state
+            .res
+            .iter()
+            .filter(|(_, obj)| obj.rtype() == rtype)
+            .map(|(id, _)| id)
+            .find(|id| {
+                self.state
+                    .try_aux_get(id)
+                    .is_some_and(|aux| aux.topic.as_deref() == Some(topic))
+            })
+            .map(|id| rtype.link_to(*id))
+    }
+
+    /// Start or stop streaming to an entertainment configuration, switching
+    /// its lights in or out of streaming mode
+    pub fn set_entertainment_status(
+        &mut self,
+        id: &Uuid,
+        status: EntertainmentConfigurationStatus,
+    ) -> ApiResult<()> {
+        let link = RType::EntertainmentConfiguration.link_to(*id);
+        let lights = self
+            .get::<EntertainmentConfiguration>(&link)?
+            .light_services
+            .clone();
+
+        let mode = match status {
+            EntertainmentConfigurationStatus::Active => LightMode::Streaming,
+            EntertainmentConfigurationStatus::Inactive => LightMode::Normal,
+        };
+
+        for light in &lights {
+            self.update::<Light>(&light.rid, |light| light.mode = mode)?;
+        }
+
+        self.update::<EntertainmentConfiguration>(id, |ent| ent.status = status)
+    }
+
+    /// The currently active entertainment configuration, if any
+    #[must_use]
+    pub fn get_active_entertainment(&self) -> Option<Uuid> {
+        self.state.res.iter().find_map(|(id, obj)| match obj {
+            Resource::EntertainmentConfiguration(ent)
+                if ent.status == EntertainmentConfigurationStatus::Active =>
+            {
+                Some(*id)
+            }
+            _ => None,
+        })
+    }
+
+    /// The grouped light service of a room, zone or bridge home
+    pub fn grouped_light_of(&self, group: &ResourceLink) -> ApiResult<ResourceLink> {
+        let glight = match group.rtype {
+            RType::Room => self.get::<Room>(group)?.grouped_light_service(),
+            RType::Zone => self.get::<Zone>(group)?.grouped_light_service(),
+            RType::BridgeHome => self
+                .get::<BridgeHome>(group)?
+                .services
+                .iter()
+                .find(|rl| rl.rtype == RType::GroupedLight),
+            _ => None,
+        };
+        glight.copied().ok_or(ApiError::NotFound(group.rid))
+    }
+
+    /// Send an update to all lights of a grouped light
+    pub fn grouped_light_request(
+        &self,
+        glight: &ResourceLink,
+        payload: z2m::update::DeviceUpdate,
+    ) -> ApiResult<()> {
+        let owner = self.get::<GroupedLight>(glight)?.owner;
+
+        /* bifrost-only zones have no z2m group, so address each light directly */
+        let has_topic = self.aux_get(&owner).is_ok_and(|aux| aux.topic.is_some());
+        if owner.rtype == RType::Zone && !has_topic {
+            let zone: &Zone = self.get(&owner)?;
+            for light in zone.children.iter().filter(|rl| rl.rtype == RType::Light) {
+                self.z2m_request(ClientRequest::light_update(*light, payload.clone()))?;
+            }
+            Ok(())
+        } else {
+            self.z2m_request(ClientRequest::group_update(*glight, payload))
+        }
+    }
+
+    /// Recall a scene, and mark it as the active scene in its room
+    pub fn recall_scene(&mut self, link: &ResourceLink, transition: Option<f64>) -> ApiResult<()> {
+        let room = self.get::<Scene>(link)?.group.rid;
+
+        for rid in self.get_scenes_for_room(&room) {
+            self.update(&rid, |scn: &mut Scene| {
+                scn.status = Some(if rid == link.rid {
+                    SceneStatus::Static
+                } else {
+                    SceneStatus::Inactive
+                });
+            })?;
+        }
+
+        self.z2m_request(ClientRequest::scene_recall(*link, transition))
+    }
+
     #[must_use]
     pub fn get_scenes_for_room(&self, id: &Uuid) -> Vec<Uuid> {
         self.state
@@ -161,6 +566,10 @@ impl Resources {
 
         self.state.insert(link.rid, obj);
 
+        if Self::affects_groups(link.rtype) {
+            self.group_index = None;
+        }
+
         self.state_updates.notify_one();
 
         let evt = EventBlock::add(serde_json::to_value(self.get_resource_by_id(&link.rid)?)?);
@@ -172,10 +581,28 @@ impl Resources {
         Ok(())
     }
 
+    /// Whether adding or removing a resource of this type can change the
+    /// members of a grouped light
+    const fn affects_groups(rtype: RType) -> bool {
+        matches!(
+            rtype,
+            RType::Device | RType::Light | RType::Room | RType::Zone | RType::GroupedLight
+        )
+    }
+
     pub fn delete(&mut self, link: &ResourceLink) -> ApiResult<()> {
         log::info!("Deleting {link:?}..");
         self.state.remove(&link.rid)?;
 
+        if Self::affects_groups(link.rtype) {
+            self.group_index = None;
+        }
+
+        /* scene ids are reused, so v1 scene data must not outlive the scene */
+        if link.rtype == RType::Scene {
+            self.state.legacy_mut().scenes.remove(&link.rid);
+        }
+
         self.state_updates.notify_one();
 
         let evt = EventBlock::delete(link)?;
@@ -185,6 +612,65 @@ impl Resources {
         Ok(())
     }
 
+    /// Searches for devices don't survive a restart, so make sure none
+    /// is reported as running
+    pub fn reset_device_discovery(&mut self) -> ApiResult<()> {
+        for rr in self.get_resources_by_type(RType::ZigbeeDeviceDiscovery) {
+            self.update(&rr.id, |zbdd: &mut ZigbeeDeviceDiscovery| {
+                zbdd.status = ZigbeeDeviceDiscoveryStatus::Ready;
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Report a search for new devices, or extend the running one
+    pub fn start_device_search(&mut self, duration: Duration) -> ApiResult<()> {
+        self.device_search = Some(Utc::now() + duration);
+        for rr in self.get_resources_by_type(RType::ZigbeeDeviceDiscovery) {
+            self.update(&rr.id, |zbdd: &mut ZigbeeDeviceDiscovery| {
+                zbdd.status = ZigbeeDeviceDiscoveryStatus::Searching;
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Report the search for new devices as finished, unless it has been
+    /// extended by a later search
+    pub fn finish_device_search(&mut self) -> ApiResult<()> {
+        if self
+            .device_search
+            .is_some_and(|deadline| Utc::now() < deadline)
+        {
+            return Ok(());
+        }
+        log::info!("Search for new devices finished");
+        self.device_search = None;
+        self.reset_device_discovery()
+    }
+
+    /// Update the zigbee connectivity of the bridge itself. Bifrost can be
+    /// connected to several z2m servers, so only the first server to report
+    /// gets to update it.
+    pub fn update_bridge_connectivity(
+        &mut self,
+        server: &str,
+        func: impl FnOnce(&mut ZigbeeConnectivity),
+    ) -> ApiResult<()> {
+        let owner = self
+            .network_server
+            .get_or_insert_with(|| server.to_string());
+        if owner != server {
+            return Ok(());
+        }
+
+        let Some(bridge) = self.get_resources_by_type(RType::Bridge).pop() else {
+            return Ok(());
+        };
+
+        let link_zbc = RType::ZigbeeConnectivity.deterministic(bridge.id);
+        self.update(&link_zbc.rid, func)
+    }
+
     pub fn add_bridge(&mut self, bridge_id: String) -> ApiResult<()> {
         let link_bridge = RType::Bridge.deterministic(&bridge_id);
         let link_bridge_home = RType::BridgeHome.deterministic(format!("{bridge_id}HOME"));
@@ -218,7 +704,7 @@ impl Resources {
 
         let zbdd = ZigbeeDeviceDiscovery {
             owner: link_bridge_dev,
-            status: String::from("ready"),
+            status: ZigbeeDeviceDiscoveryStatus::Ready,
         };
 
         let zbc = ZigbeeConnectivity {
@@ -229,7 +715,7 @@ impl Resources {
                 "status": "set",
                 "value": "channel_25",
             })),
-            extended_pan_id: String::from("0123456789abcdef"),
+            extended_pan_id: Some(String::from("0123456789abcdef")),
         };
 
         self.add(&link_bridge_dev, Resource::Device(bridge_dev))?;
@@ -288,10 +774,13 @@ impl Resources {
     grouped_light             /groups/{id}
     homekit                   null
     light                     /lights/{id}
+    light_level               /sensors/{id}
     matter                    null
+    motion                    /sensors/{id}
     room                      /groups/{id}
     scene                     /scenes/{id}
     smart_scene               null
+    temperature               /sensors/{id}
     zigbee_connectivity       /lights/{id}
     zigbee_connectivity       null
     zigbee_device_discovery   null
@@ -304,6 +793,9 @@ impl Resources {
             Resource::GroupedLight(_) => Some(format!("/groups/{id}")),
             Resource::Light(_) => Some(format!("/lights/{id}")),
             Resource::Scene(_) => Some(format!("/scenes/{id}")),
+            Resource::LightLevel(_) | Resource::Motion(_) | Resource::Temperature(_) => {
+                Some(format!("/sensors/{id}"))
+            }
 
             /* Rooms map to their grouped_light service's id_v1 */
             Resource::Room(room) => room
@@ -328,6 +820,7 @@ impl Resources {
             | Resource::BehaviorScript(_)
             | Resource::Bridge(_)
             | Resource::Entertainment(_)
+            | Resource::EntertainmentConfiguration(_)
             | Resource::GeofenceClient(_)
             | Resource::Geolocation(_)
             | Resource::Homekit(_)
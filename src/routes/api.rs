@@ -3,21 +3,30 @@ use std::collections::HashMap;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 
 use bytes::Bytes;
+use chrono::{Local, Utc};
 use log::{info, warn};
 use serde_json::{json, Value};
 use tokio::sync::MutexGuard;
 use uuid::Uuid;
 
-use crate::hue::api::{Device, GroupedLight, Light, RType, ResourceLink, Room, Scene, V1Reply};
+use crate::hue::api::{
+    Device, GroupedLight, Light, RType, Resource, ResourceLink, Room, Scene, SceneAction,
+    SceneActionElement, SceneMetadata, SceneStatus, V1Reply, ZigbeeConnectivity,
+    ZigbeeConnectivityStatus,
+};
 use crate::hue::legacy_api::{
-    ApiGroup, ApiLight, ApiLightStateUpdate, ApiResourceType, ApiScene, ApiUserConfig,
-    Capabilities, HueResult, NewUser, NewUserReply,
+    ApiCommand, ApiCommandMethod, ApiConfigUpdate, ApiGroup, ApiLight, ApiLightStateUpdate,
+    ApiResourceLink, ApiResourceLinkNew, ApiResourceLinkUpdate, ApiResourceType, ApiRule,
+    ApiRuleNew, ApiRuleUpdate, ApiScene, ApiSceneData, ApiSceneNew, ApiSceneType, ApiSceneUpdate,
+    ApiSchedule, ApiScheduleNew, ApiScheduleUpdate, ApiSensor, ApiStatus, ApiUserConfig,
+    Capabilities, HueError, HueResult, NewUser, NewUserReply,
 };
+use crate::model::state::{ApiUser, AuxData, LegacyData};
 use crate::resource::Resources;
 use crate::server::appstate::AppState;
 use crate::z2m::request::ClientRequest;
@@ -31,41 +40,72 @@ async fn get_api_config(State(state): State<AppState>) -> impl IntoResponse {
     Json(state.api_short_config())
 }
 
-async fn post_api(bytes: Bytes) -> ApiResult<impl IntoResponse> {
+async fn post_api(State(state): State<AppState>, bytes: Bytes) -> ApiResult<impl IntoResponse> {
     let json: NewUser = serde_json::from_slice(&bytes)?;
     info!("post: {json:?}");
+
+    let mut lock = state.res.lock().await;
+
+    if !lock.link_button_pressed() {
+        warn!(
+            "Rejected new user {:?}: link button not pressed",
+            json.devicetype
+        );
+        return Ok(Json(vec![HueResult::Error(
+            HueError::link_button_not_pressed(),
+        )]));
+    }
+
+    let username = Uuid::new_v4();
+    let user = ApiUser::new(&json.devicetype);
+
     let res = NewUserReply {
-        clientkey: Uuid::new_v4(),
-        username: Uuid::new_v4(),
+        clientkey: json
+            .generateclientkey
+            .unwrap_or_default()
+            .then(|| user.client_key.simple().to_string().to_uppercase()),
+        username,
     };
+
+    lock.add_user(username, user);
+    drop(lock);
+
     Ok(Json(vec![HueResult::Success(res)]))
 }
 
-fn get_lights(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiLight>> {
+pub fn get_lights(res: &Resources) -> ApiResult<HashMap<String, ApiLight>> {
     let mut lights = HashMap::new();
 
     for rr in res.get_resources_by_type(RType::Light) {
         let light: Light = rr.obj.try_into()?;
         let dev = res.get::<Device>(&light.owner)?;
+        let reachable = dev
+            .services
+            .iter()
+            .find(|svc| svc.rtype == RType::ZigbeeConnectivity)
+            .and_then(|svc| res.get::<ZigbeeConnectivity>(svc).ok())
+            .map_or(true, |zbc| {
+                zbc.status == ZigbeeConnectivityStatus::Connected
+            });
         lights.insert(
             res.get_id_v1(rr.id)?,
-            ApiLight::from_dev_and_light(&rr.id, dev, &light),
+            ApiLight::from_dev_and_light(&rr.id, dev, &light).with_reachable(reachable),
         );
     }
 
     Ok(lights)
 }
 
-fn get_groups(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiGroup>> {
+pub fn get_groups(res: &Resources) -> ApiResult<HashMap<String, ApiGroup>> {
     let mut rooms = HashMap::new();
 
     for rr in res.get_resources_by_type(RType::Room) {
         let room: Room = rr.obj.try_into()?;
-        let uuid = room
-            .services
-            .iter()
-            .find(|rl| rl.rtype == RType::GroupedLight)
-            .ok_or(ApiError::NotFound(rr.id))?;
+
+        /* new rooms get their grouped light once z2m reports the group */
+        let Some(uuid) = room.grouped_light_service() else {
+            continue;
+        };
 
         let glight = res.get::<GroupedLight>(uuid)?.clone();
         let lights: Vec<String> = room
@@ -85,22 +125,55 @@ fn get_groups(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiGroup
     Ok(rooms)
 }
 
+fn get_sensor(res: &Resources, uuid: &Uuid) -> ApiResult<Option<ApiSensor>> {
+    let sensor = match &res.get_resource_by_id(uuid)?.obj {
+        Resource::Motion(motion) => {
+            let dev = res.get::<Device>(&motion.owner)?;
+            ApiSensor::from_dev_and_motion(uuid, dev, motion)
+        }
+        Resource::LightLevel(light_level) => {
+            let dev = res.get::<Device>(&light_level.owner)?;
+            ApiSensor::from_dev_and_light_level(uuid, dev, light_level)
+        }
+        Resource::Temperature(temperature) => {
+            let dev = res.get::<Device>(&temperature.owner)?;
+            ApiSensor::from_dev_and_temperature(uuid, dev, temperature)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(sensor))
+}
+
+pub fn get_sensors(res: &Resources) -> ApiResult<HashMap<String, ApiSensor>> {
+    let mut sensors = HashMap::new();
+
+    for rtype in [RType::Motion, RType::LightLevel, RType::Temperature] {
+        for rr in res.get_resources_by_type(rtype) {
+            if let Some(sensor) = get_sensor(res, &rr.id)? {
+                sensors.insert(res.get_id_v1(rr.id)?, sensor);
+            }
+        }
+    }
+
+    Ok(sensors)
+}
+
 fn get_scenes(owner: &String, res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiScene>> {
     let mut scenes = HashMap::new();
 
     for rr in res.get_resources_by_type(RType::Scene) {
-        let scene = &rr.obj.try_into()?;
+        let scene: Scene = rr.obj.try_into()?;
 
         scenes.insert(
             res.get_id_v1(rr.id)?,
-            ApiScene::from_scene(res, owner, scene)?,
+            ApiScene::from_scene(res, owner, &rr.id, &scene)?,
         );
     }
 
     Ok(scenes)
 }
 
-#[allow(clippy::zero_sized_map_values)]
 async fn get_api_user(
     state: State<AppState>,
     Path(username): Path<String>,
@@ -108,14 +181,14 @@ async fn get_api_user(
     let lock = state.res.lock().await;
 
     Ok(Json(ApiUserConfig {
-        config: state.api_config(&username),
+        config: state.api_config(&lock),
         groups: get_groups(&lock)?,
         lights: get_lights(&lock)?,
-        resourcelinks: HashMap::new(),
-        rules: HashMap::new(),
+        resourcelinks: lock.legacy().resourcelinks.clone(),
+        rules: lock.legacy().rules.clone(),
         scenes: get_scenes(&username, &lock)?,
-        schedules: HashMap::new(),
-        sensors: HashMap::new(),
+        schedules: lock.legacy().schedules.clone(),
+        sensors: get_sensors(&lock)?,
     }))
 }
 
@@ -125,34 +198,127 @@ async fn get_api_user_resource(
 ) -> ApiResult<Json<Value>> {
     let lock = &state.res.lock().await;
     match resource {
-        ApiResourceType::Config => Ok(Json(json!(state.api_config(&username)))),
+        ApiResourceType::Config => {
+            /* unknown users only get to see the short config */
+            let user = Uuid::parse_str(&username).ok();
+            if user.is_some_and(|uuid| lock.get_user(&uuid).is_some()) {
+                Ok(Json(json!(state.api_config(lock))))
+            } else {
+                Ok(Json(json!(state.api_short_config())))
+            }
+        }
         ApiResourceType::Lights => Ok(Json(json!(get_lights(lock)?))),
         ApiResourceType::Groups => Ok(Json(json!(get_groups(lock)?))),
         ApiResourceType::Scenes => Ok(Json(json!(get_scenes(&username, lock)?))),
-        ApiResourceType::Resourcelinks
-        | ApiResourceType::Rules
-        | ApiResourceType::Schedules
-        | ApiResourceType::Sensors => Ok(Json(json!({}))),
+        ApiResourceType::Sensors => Ok(Json(json!(get_sensors(lock)?))),
+        ApiResourceType::Resourcelinks => Ok(Json(json!(lock.legacy().resourcelinks))),
+        ApiResourceType::Rules => Ok(Json(json!(lock.legacy().rules))),
+        ApiResourceType::Schedules => Ok(Json(json!(lock.legacy().schedules))),
         ApiResourceType::Capabilities => Ok(Json(json!(Capabilities::new()))),
     }
 }
 
 async fn post_api_user_resource(
-    Path((_username, resource)): Path<(String, ApiResourceType)>,
+    State(state): State<AppState>,
+    Path((username, resource)): Path<(String, ApiResourceType)>,
     Json(req): Json<Value>,
 ) -> ApiResult<Json<Value>> {
-    warn!("POST v1 user resource unsupported");
-    warn!("Request: {req:?}");
-    Err(ApiError::V1CreateUnsupported(resource))
+    log::debug!("POST v1 resource={resource:?} {req:?}");
+
+    let mut lock = state.res.lock().await;
+    let now = Local::now().naive_local();
+
+    let id = match resource {
+        ApiResourceType::Scenes => post_scene(&mut lock, username, req)?,
+        ApiResourceType::Schedules => {
+            let new: ApiScheduleNew = serde_json::from_value(req)?;
+            let mut schedule = ApiSchedule {
+                name: new.name,
+                description: new.description,
+                command: new.command,
+                localtime: new.localtime,
+                created: Utc::now(),
+                status: new.status,
+                autodelete: new.autodelete,
+                starttime: None,
+                recycle: new.recycle,
+            };
+            schedule.start(now)?;
+
+            let schedules = &mut lock.legacy_mut().schedules;
+            let id = LegacyData::next_id(schedules);
+            schedules.insert(id, schedule);
+            id
+        }
+        ApiResourceType::Rules => {
+            let new: ApiRuleNew = serde_json::from_value(req)?;
+            let rule = ApiRule {
+                name: new.name,
+                owner: username,
+                created: Utc::now(),
+                lasttriggered: String::from("none"),
+                timestriggered: 0,
+                status: new.status,
+                recycle: new.recycle,
+                conditions: new.conditions,
+                actions: new.actions,
+            };
+
+            let rules = &mut lock.legacy_mut().rules;
+            let id = LegacyData::next_id(rules);
+            rules.insert(id, rule);
+            id
+        }
+        ApiResourceType::Resourcelinks => {
+            let new: ApiResourceLinkNew = serde_json::from_value(req)?;
+            let link = ApiResourceLink {
+                name: new.name,
+                description: new.description,
+                link_type: String::from("Link"),
+                classid: new.classid,
+                owner: username,
+                recycle: new.recycle,
+                links: new.links,
+            };
+
+            let resourcelinks = &mut lock.legacy_mut().resourcelinks;
+            let id = LegacyData::next_id(resourcelinks);
+            resourcelinks.insert(id, link);
+            id
+        }
+        _ => {
+            warn!("POST v1 user resource unsupported");
+            warn!("Request: {req:?}");
+            return Err(ApiError::V1CreateUnsupported(resource));
+        }
+    };
+    drop(lock);
+
+    log::info!("Created v1 {resource:?} {id}");
+
+    Ok(Json(json!([{"success": {"id": id.to_string()}}])))
 }
 
 async fn put_api_user_resource(
-    Path((_username, _resource)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Path((_username, resource)): Path<(String, ApiResourceType)>,
     Json(req): Json<Value>,
-) -> impl IntoResponse {
-    warn!("PUT v1 user resource {req:?}");
-    //Json(format!("user {username} resource {resource}"))
-    Json(vec![HueResult::Success(req)])
+) -> ApiResult<Json<Value>> {
+    let ApiResourceType::Config = resource else {
+        warn!("PUT v1 user resource {req:?}");
+        return Ok(Json(json!([HueResult::Success(req)])));
+    };
+
+    let upd: ApiConfigUpdate = serde_json::from_value(req)?;
+
+    if upd.linkbutton == Some(true) {
+        let duration = state.config().bridge.linkbutton.press_duration(None);
+        state.res.lock().await.press_link_button(duration);
+    }
+
+    let reply = V1Reply::new(String::from("/config")).add_option("linkbutton", upd.linkbutton)?;
+
+    Ok(Json(reply.json()))
 }
 
 #[allow(clippy::significant_drop_tightening)]
@@ -177,7 +343,7 @@ async fn get_api_user_resource_id(
             let link = ResourceLink::new(uuid, RType::Scene);
             let scene = lock.get::<Scene>(&link)?;
 
-            json!(ApiScene::from_scene(&lock, &username, scene)?)
+            json!(ApiScene::from_scene(&lock, &username, &uuid, scene)?)
         }
         ApiResourceType::Groups => {
             let lock = state.res.lock().await;
@@ -188,81 +354,128 @@ async fn get_api_user_resource_id(
 
             json!(group)
         }
+        ApiResourceType::Sensors => {
+            let lock = state.res.lock().await;
+            let uuid = lock.from_id_v1(id)?;
+            let sensor = get_sensor(&lock, &uuid)?.ok_or(ApiError::V1NotFound(id))?;
+
+            json!(sensor)
+        }
+        ApiResourceType::Schedules => {
+            let lock = state.res.lock().await;
+            json!(lock
+                .legacy()
+                .schedules
+                .get(&id)
+                .ok_or(ApiError::V1NotFound(id))?)
+        }
+        ApiResourceType::Rules => {
+            let lock = state.res.lock().await;
+            json!(lock
+                .legacy()
+                .rules
+                .get(&id)
+                .ok_or(ApiError::V1NotFound(id))?)
+        }
+        ApiResourceType::Resourcelinks => {
+            let lock = state.res.lock().await;
+            json!(lock
+                .legacy()
+                .resourcelinks
+                .get(&id)
+                .ok_or(ApiError::V1NotFound(id))?)
+        }
         _ => Err(ApiError::V1NotFound(id))?,
     };
 
     Ok(Json(result))
 }
 
-async fn put_api_user_resource_id(
-    State(state): State<AppState>,
-    Path((_username, resource, id, path)): Path<(String, ApiResourceType, u32, String)>,
-    Json(req): Json<Value>,
-) -> ApiResult<Json<Value>> {
+/// Apply a light state or group action, as sent by clients directly, or
+/// embedded in schedules and rules
+pub fn put_resource_path(
+    res: &Resources,
+    resource: ApiResourceType,
+    id: u32,
+    path: &str,
+    req: Value,
+) -> ApiResult<Value> {
+    log::debug!("req: {}", serde_json::to_string_pretty(&req)?);
+
     match resource {
         ApiResourceType::Lights => {
-            log::debug!("req: {}", serde_json::to_string_pretty(&req)?);
             if path != "state" {
-                return Err(ApiError::V1NotFound(id))?;
+                return Err(ApiError::V1NotFound(id));
             }
 
-            let lock = state.res.lock().await;
-            let uuid = lock.from_id_v1(id)?;
+            let uuid = res.from_id_v1(id)?;
             let link = ResourceLink::new(uuid, RType::Light);
             let upd: ApiLightStateUpdate = serde_json::from_value(req)?;
+            let current = res
+                .get::<Light>(&link)
+                .ok()
+                .and_then(|light| Some(light.color.as_ref()?.xy));
 
             let payload = DeviceUpdate::default()
                 .with_state(upd.on)
                 .with_brightness(upd.bri.map(f64::from))
-                .with_color_xy(upd.xy.map(Into::into))
-                .with_color_temp(upd.ct);
+                .with_color_xy(upd.color_xy(current))
+                .with_color_temp(upd.ct)
+                .with_transition(upd.transition())
+                .with_effect(upd.z2m_effect());
 
-            lock.z2m_request(ClientRequest::light_update(link, payload))?;
-            drop(lock);
+            res.z2m_request(ClientRequest::light_update(link, payload))?;
 
-            let reply = V1Reply::for_light(id, &path).with_light_state_update(&upd)?;
+            let reply = V1Reply::for_light(id, path).with_light_state_update(&upd)?;
 
-            Ok(Json(reply.json()))
+            Ok(reply.json())
         }
         ApiResourceType::Groups => {
-            log::debug!("req: {}", serde_json::to_string_pretty(&req)?);
             if path != "action" {
-                return Err(ApiError::V1NotFound(id))?;
+                return Err(ApiError::V1NotFound(id));
             }
 
-            let lock = state.res.lock().await;
-            let uuid = lock.from_id_v1(id)?;
+            let uuid = res.from_id_v1(id)?;
             let link = ResourceLink::new(uuid, RType::Room);
-            let room: &Room = lock.get(&link)?;
-            let glight = room.grouped_light_service().unwrap();
+            let room: &Room = res.get(&link)?;
+            let glight = room
+                .grouped_light_service()
+                .ok_or(ApiError::V1NotFound(id))?;
 
             let upd: ApiGroupActionUpdate = serde_json::from_value(req)?;
 
             let reply = match upd {
                 ApiGroupActionUpdate::LightUpdate(upd) => {
+                    /* a lone hue or saturation applies to the current color */
+                    let current = res
+                        .lights_of_group(&link)?
+                        .iter()
+                        .filter_map(|light| res.get::<Light>(light).ok())
+                        .find_map(Light::as_color_opt);
+
                     let payload = DeviceUpdate::default()
                         .with_state(upd.on)
                         .with_brightness(upd.bri.map(f64::from))
-                        .with_color_xy(upd.xy.map(Into::into))
-                        .with_color_temp(upd.ct);
+                        .with_color_xy(upd.color_xy(current))
+                        .with_color_temp(upd.ct)
+                        .with_transition(upd.transition())
+                        .with_effect(upd.z2m_effect());
 
-                    lock.z2m_request(ClientRequest::group_update(*glight, payload))?;
-                    drop(lock);
+                    res.z2m_request(ClientRequest::group_update(*glight, payload))?;
 
-                    V1Reply::for_group(id, &path).with_light_state_update(&upd)?
+                    V1Reply::for_group(id, path).with_light_state_update(&upd)?
                 }
                 ApiGroupActionUpdate::GroupUpdate(upd) => {
                     let scene_id = upd.scene.parse()?;
-                    let scene_uuid = lock.from_id_v1(scene_id)?;
+                    let scene_uuid = res.from_id_v1(scene_id)?;
                     let rlink = RType::Scene.link_to(scene_uuid);
-                    lock.z2m_request(ClientRequest::scene_recall(rlink))?;
-                    drop(lock);
+                    res.z2m_request(ClientRequest::scene_recall(rlink, upd.transition()))?;
 
-                    V1Reply::for_group(id, &path).add("scene", upd.scene)?
+                    V1Reply::for_group(id, path).add("scene", upd.scene)?
                 }
             };
 
-            Ok(Json(reply.json()))
+            Ok(reply.json())
         }
         ApiResourceType::Config
         | ApiResourceType::Resourcelinks
@@ -274,14 +487,515 @@ async fn put_api_user_resource_id(
     }
 }
 
+fn light_link(res: &Resources, id: &str) -> ApiResult<ResourceLink> {
+    Ok(RType::Light.link_to(res.from_id_v1(id.parse()?)?))
+}
+
+fn light_links(res: &Resources, lights: &[String]) -> ApiResult<Vec<ResourceLink>> {
+    lights.iter().map(|id| light_link(res, id)).collect()
+}
+
+/// The room a light belongs to, since every scene needs a group
+fn room_of_light(res: &Resources, light: &ResourceLink) -> ApiResult<ResourceLink> {
+    let owner = res.get::<Light>(light)?.owner;
+
+    res.get_resources_by_type(RType::Room)
+        .into_iter()
+        .find_map(|rr| {
+            let room: Room = rr.obj.try_into().ok()?;
+            room.children
+                .contains(&owner)
+                .then(|| RType::Room.link_to(rr.id))
+        })
+        .ok_or(ApiError::NotFound(light.rid))
+}
+
+/// The current state of the given lights, as scene actions
+fn current_actions(
+    res: &Resources,
+    lights: &[ResourceLink],
+    lightstates: &HashMap<String, ApiLightStateUpdate>,
+) -> ApiResult<Vec<SceneActionElement>> {
+    lights
+        .iter()
+        .map(|target| {
+            let light = res.get::<Light>(target)?;
+            let action = lightstates.get(&res.get_id_v1(target.rid)?).map_or_else(
+                || SceneAction::from(light),
+                |upd| upd.scene_action(light.as_color_opt()),
+            );
+            Ok(SceneActionElement {
+                action,
+                target: *target,
+            })
+        })
+        .collect()
+}
+
+fn post_scene(res: &mut Resources, owner: String, req: Value) -> ApiResult<u32> {
+    let new: ApiSceneNew = serde_json::from_value(req)?;
+
+    let room = match (&new.group, new.lights.first()) {
+        (Some(group), _) => RType::Room.link_to(res.from_id_v1(group.parse()?)?),
+        (None, Some(light)) => room_of_light(res, &light_link(res, light)?)?,
+        (None, None) => return Err(ApiError::V1InvalidRequest("scene has no lights or group")),
+    };
+
+    let lights = if new.lights.is_empty() {
+        let room_obj: &Room = res.get(&room)?;
+        room_obj
+            .children
+            .iter()
+            .filter_map(|rl| res.get(rl).ok())
+            .filter_map(Device::light_service)
+            .copied()
+            .collect()
+    } else {
+        light_links(res, &new.lights)?
+    };
+
+    let actions = current_actions(res, &lights, &new.lightstates)?;
+
+    let sid = res.get_next_scene_id(&room)?;
+    let link_scene = RType::Scene.deterministic((room.rid, sid));
+
+    log::info!("New v1 scene: {link_scene:?} ({})", new.name);
+
+    res.aux_set(
+        &link_scene,
+        AuxData::new().with_topic(&new.name).with_index(sid),
+    );
+
+    let scene = Scene {
+        actions,
+        auto_dynamic: false,
+        group: room,
+        metadata: SceneMetadata {
+            appdata: None,
+            image: None,
+            name: new.name,
+        },
+        palette: json!({
+            "color": [],
+            "dimming": [],
+            "color_temperature": [],
+            "effects": [],
+        }),
+        speed: 0.5,
+        status: Some(SceneStatus::Inactive),
+    };
+
+    let scene_type = match (new.scene_type, &new.group) {
+        (Some(scene_type), _) => scene_type,
+        (None, Some(_)) => ApiSceneType::GroupScene,
+        (None, None) => ApiSceneType::LightScene,
+    };
+
+    res.add(&link_scene, Resource::Scene(scene))?;
+    res.legacy_mut().scenes.insert(
+        link_scene.rid,
+        ApiSceneData {
+            owner: Some(owner),
+            recycle: new.recycle,
+            appdata: new.appdata,
+            picture: new.picture,
+            ..ApiSceneData::new(scene_type)
+        },
+    );
+    res.z2m_request(ClientRequest::scene_add(link_scene))?;
+
+    res.get_id_v1_index(link_scene.rid)
+}
+
+fn put_scene(res: &mut Resources, id: u32, prefix: String, req: Value) -> ApiResult<Value> {
+    let upd: ApiSceneUpdate = serde_json::from_value(req)?;
+    let uuid = res.from_id_v1(id)?;
+    let link = RType::Scene.link_to(uuid);
+    let scene: &Scene = res.get(&link)?;
+
+    /* changing the lights keeps existing light states, while
+     * "storelightstate" captures the current state of all lights */
+    let store = upd.storelightstate == Some(true);
+    let actions = if upd.lights.is_some() || store {
+        let lights = match &upd.lights {
+            Some(lights) => light_links(res, lights)?,
+            None => scene.actions.iter().map(|sae| sae.target).collect(),
+        };
+        let mut actions = current_actions(res, &lights, &HashMap::new())?;
+        if !store {
+            for sae in &mut actions {
+                if let Some(old) = scene.actions.iter().find(|old| old.target == sae.target) {
+                    sae.action = old.action.clone();
+                }
+            }
+        }
+        Some(actions)
+    } else {
+        None
+    };
+
+    let resend = actions.is_some() || upd.name.is_some();
+
+    res.update(&uuid, |scn: &mut Scene| {
+        if let Some(name) = &upd.name {
+            scn.metadata.name.clone_from(name);
+        }
+        if let Some(actions) = actions {
+            scn.actions = actions;
+        }
+    })?;
+
+    if resend {
+        res.z2m_request(ClientRequest::scene_add(link))?;
+    }
+
+    let data = res
+        .legacy_mut()
+        .scenes
+        .entry(uuid)
+        .or_insert_with(|| ApiSceneData::new(ApiSceneType::GroupScene));
+    if let Some(recycle) = upd.recycle {
+        data.recycle = recycle;
+    }
+    if upd.appdata.is_some() {
+        data.appdata.clone_from(&upd.appdata);
+    }
+    if let Some(picture) = &upd.picture {
+        data.picture.clone_from(picture);
+    }
+    data.lastupdated = Utc::now();
+
+    let reply = V1Reply::new(prefix)
+        .add_option("name", upd.name)?
+        .add_option("lights", upd.lights)?
+        .add_option("storelightstate", upd.storelightstate)?
+        .add_option("recycle", upd.recycle)?
+        .add_option("appdata", upd.appdata)?
+        .add_option("picture", upd.picture)?;
+
+    Ok(reply.json())
+}
+
+fn put_scene_lightstate(res: &mut Resources, id: u32, light: &str, req: Value) -> ApiResult<Value> {
+    let upd: ApiLightStateUpdate = serde_json::from_value(req)?;
+    let uuid = res.from_id_v1(id)?;
+    let link = RType::Scene.link_to(uuid);
+    let target = light_link(res, light)?;
+
+    /* a lone hue or saturation applies to the color already in the scene */
+    let current = res
+        .get::<Scene>(&link)?
+        .actions
+        .iter()
+        .find(|sae| sae.target == target)
+        .and_then(|sae| sae.action.color.as_ref().map(|col| col.xy))
+        .or(res.get::<Light>(&target)?.as_color_opt());
+
+    let action = upd.scene_action(current);
+
+    res.update(&uuid, |scn: &mut Scene| {
+        match scn.actions.iter_mut().find(|sae| sae.target == target) {
+            Some(sae) => sae.action += action,
+            None => scn.actions.push(SceneActionElement { action, target }),
+        }
+    })?;
+
+    res.z2m_request(ClientRequest::scene_add(link))?;
+
+    res.legacy_mut()
+        .scenes
+        .entry(uuid)
+        .or_insert_with(|| ApiSceneData::new(ApiSceneType::GroupScene))
+        .lastupdated = Utc::now();
+
+    let reply =
+        V1Reply::new(format!("/scenes/{id}/lightstates/{light}")).with_light_state_update(&upd)?;
+
+    Ok(reply.json())
+}
+
+fn delete_scene(res: &mut Resources, id: u32) -> ApiResult<()> {
+    let uuid = res.from_id_v1(id)?;
+    let link = RType::Scene.link_to(uuid);
+    res.get::<Scene>(&link)?;
+
+    /* the scene resource is removed when z2m reports the change */
+    res.z2m_request(ClientRequest::scene_remove(link))?;
+    res.legacy_mut().scenes.remove(&uuid);
+
+    Ok(())
+}
+
+fn put_schedule(res: &mut Resources, id: u32, prefix: String, req: Value) -> ApiResult<Value> {
+    let upd: ApiScheduleUpdate = serde_json::from_value(req)?;
+    let mut schedule = res
+        .legacy()
+        .schedules
+        .get(&id)
+        .ok_or(ApiError::V1NotFound(id))?
+        .clone();
+
+    /* timers restart when (re)enabled or changed */
+    let restart = upd.localtime.is_some() || upd.status == Some(ApiStatus::Enabled);
+
+    if let Some(name) = &upd.name {
+        schedule.name.clone_from(name);
+    }
+    if let Some(description) = &upd.description {
+        schedule.description.clone_from(description);
+    }
+    if let Some(command) = &upd.command {
+        schedule.command = command.clone();
+    }
+    if let Some(localtime) = &upd.localtime {
+        schedule.localtime.clone_from(localtime);
+    }
+    if let Some(status) = upd.status {
+        schedule.status = status;
+    }
+    if upd.autodelete.is_some() {
+        schedule.autodelete = upd.autodelete;
+    }
+    if restart {
+        schedule.start(Local::now().naive_local())?;
+    }
+
+    res.legacy_mut().schedules.insert(id, schedule);
+
+    let reply = V1Reply::new(prefix)
+        .add_option("name", upd.name)?
+        .add_option("description", upd.description)?
+        .add_option("command", upd.command)?
+        .add_option("localtime", upd.localtime)?
+        .add_option("status", upd.status)?
+        .add_option("autodelete", upd.autodelete)?;
+
+    Ok(reply.json())
+}
+
+/// Update a scene, schedule, rule or resourcelink
+pub fn put_resource_id(
+    res: &mut Resources,
+    resource: ApiResourceType,
+    id: u32,
+    req: Value,
+) -> ApiResult<Value> {
+    log::debug!("req: {}", serde_json::to_string_pretty(&req)?);
+
+    let prefix = format!("/{}/{id}", resource.as_str());
+
+    match resource {
+        ApiResourceType::Scenes => put_scene(res, id, prefix, req),
+        ApiResourceType::Schedules => put_schedule(res, id, prefix, req),
+        ApiResourceType::Rules => {
+            let upd: ApiRuleUpdate = serde_json::from_value(req)?;
+            let rule = res
+                .legacy_mut()
+                .rules
+                .get_mut(&id)
+                .ok_or(ApiError::V1NotFound(id))?;
+
+            if let Some(name) = &upd.name {
+                rule.name.clone_from(name);
+            }
+            if let Some(status) = upd.status {
+                rule.status = status;
+            }
+            if let Some(recycle) = upd.recycle {
+                rule.recycle = recycle;
+            }
+            if let Some(conditions) = &upd.conditions {
+                rule.conditions.clone_from(conditions);
+            }
+            if let Some(actions) = &upd.actions {
+                rule.actions.clone_from(actions);
+            }
+
+            let reply = V1Reply::new(prefix)
+                .add_option("name", upd.name)?
+                .add_option("status", upd.status)?
+                .add_option("recycle", upd.recycle)?
+                .add_option("conditions", upd.conditions)?
+                .add_option("actions", upd.actions)?;
+
+            Ok(reply.json())
+        }
+        ApiResourceType::Resourcelinks => {
+            let upd: ApiResourceLinkUpdate = serde_json::from_value(req)?;
+            let link = res
+                .legacy_mut()
+                .resourcelinks
+                .get_mut(&id)
+                .ok_or(ApiError::V1NotFound(id))?;
+
+            if let Some(name) = &upd.name {
+                link.name.clone_from(name);
+            }
+            if let Some(description) = &upd.description {
+                link.description.clone_from(description);
+            }
+            if let Some(classid) = upd.classid {
+                link.classid = classid;
+            }
+            if let Some(recycle) = upd.recycle {
+                link.recycle = recycle;
+            }
+            if let Some(links) = &upd.links {
+                link.links.clone_from(links);
+            }
+
+            let reply = V1Reply::new(prefix)
+                .add_option("name", upd.name)?
+                .add_option("description", upd.description)?
+                .add_option("classid", upd.classid)?
+                .add_option("recycle", upd.recycle)?
+                .add_option("links", upd.links)?;
+
+            Ok(reply.json())
+        }
+        _ => Err(ApiError::V1CreateUnsupported(resource)),
+    }
+}
+
+/// Update an item below a resource (e.g. `/scenes/:id/lightstates/:light`)
+pub fn put_resource_item(
+    res: &mut Resources,
+    resource: ApiResourceType,
+    id: u32,
+    key: &str,
+    item: &str,
+    req: Value,
+) -> ApiResult<Value> {
+    match (resource, key) {
+        (ApiResourceType::Scenes, "lightstates") => put_scene_lightstate(res, id, item, req),
+        _ => Err(ApiError::V1NotFound(id)),
+    }
+}
+
+/// Delete a scene, schedule, rule or resourcelink
+pub fn delete_resource_id(
+    res: &mut Resources,
+    resource: ApiResourceType,
+    id: u32,
+) -> ApiResult<Value> {
+    let found = match resource {
+        ApiResourceType::Scenes => {
+            delete_scene(res, id)?;
+            true
+        }
+        ApiResourceType::Schedules => res.legacy_mut().schedules.remove(&id).is_some(),
+        ApiResourceType::Rules => res.legacy_mut().rules.remove(&id).is_some(),
+        ApiResourceType::Resourcelinks => res.legacy_mut().resourcelinks.remove(&id).is_some(),
+        _ => return Err(ApiError::V1CreateUnsupported(resource)),
+    };
+
+    if !found {
+        return Err(ApiError::V1NotFound(id));
+    }
+
+    log::info!("Deleted v1 {resource:?} {id}");
+
+    Ok(json!([{"success": format!("/{}/{id} deleted", resource.as_str())}]))
+}
+
+/// Execute a command from a schedule or rule, as if it was sent to the v1 api
+pub fn run_command(res: &mut Resources, cmd: &ApiCommand) -> ApiResult<Value> {
+    let unsupported =
+        || ApiError::V1CommandUnsupported(format!("{:?}", cmd.method), cmd.address.clone());
+
+    let path = cmd.path();
+    let (Some(rtype), Some(id)) = (path.first(), path.get(1)) else {
+        return Err(unsupported());
+    };
+
+    let resource: ApiResourceType =
+        serde_json::from_value(json!(rtype)).map_err(|_| unsupported())?;
+    let id: u32 = id.parse().map_err(|_| unsupported())?;
+
+    match (cmd.method, &path[2..]) {
+        (ApiCommandMethod::Put, [key]) => {
+            put_resource_path(res, resource, id, key, cmd.body.clone())
+        }
+        (ApiCommandMethod::Put, [key, item]) => {
+            put_resource_item(res, resource, id, key, item, cmd.body.clone())
+        }
+        (ApiCommandMethod::Put, []) => put_resource_id(res, resource, id, cmd.body.clone()),
+        (ApiCommandMethod::Delete, []) => delete_resource_id(res, resource, id),
+        _ => Err(unsupported()),
+    }
+}
+
+async fn put_api_user_resource_id(
+    State(state): State<AppState>,
+    Path((_username, resource, id)): Path<(String, ApiResourceType, u32)>,
+    Json(req): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    let mut lock = state.res.lock().await;
+    let reply = put_resource_id(&mut lock, resource, id, req)?;
+    drop(lock);
+
+    Ok(Json(reply))
+}
+
+async fn put_api_user_resource_id_path(
+    State(state): State<AppState>,
+    Path((_username, resource, id, path)): Path<(String, ApiResourceType, u32, String)>,
+    Json(req): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    let lock = state.res.lock().await;
+    let reply = put_resource_path(&lock, resource, id, &path, req)?;
+    drop(lock);
+
+    Ok(Json(reply))
+}
+
+async fn put_api_user_resource_id_path_item(
+    State(state): State<AppState>,
+    Path((_username, resource, id, key, item)): Path<(
+        String,
+        ApiResourceType,
+        u32,
+        String,
+        String,
+    )>,
+    Json(req): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    let mut lock = state.res.lock().await;
+    let reply = put_resource_item(&mut lock, resource, id, &key, &item, req)?;
+    drop(lock);
+
+    Ok(Json(reply))
+}
+
+async fn delete_api_user_resource_id(
+    State(state): State<AppState>,
+    Path((_username, resource, id)): Path<(String, ApiResourceType, u32)>,
+) -> ApiResult<Json<Value>> {
+    let mut lock = state.res.lock().await;
+    let reply = delete_resource_id(&mut lock, resource, id)?;
+    drop(lock);
+
+    Ok(Json(reply))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", post(post_api))
         .route("/config", get(get_api_config))
+}
+
+pub fn user_router() -> Router<AppState> {
+    Router::new()
         .route("/:user", get(get_api_user))
         .route("/:user/:rtype", get(get_api_user_resource))
         .route("/:user/:rtype", post(post_api_user_resource))
         .route("/:user/:rtype", put(put_api_user_resource))
         .route("/:user/:rtype/:id", get(get_api_user_resource_id))
-        .route("/:user/:rtype/:id/:key", put(put_api_user_resource_id))
+        .route("/:user/:rtype/:id", put(put_api_user_resource_id))
+        .route("/:user/:rtype/:id", delete(delete_api_user_resource_id))
+        .route("/:user/:rtype/:id/:key", put(put_api_user_resource_id_path))
+        .route(
+            "/:user/:rtype/:id/:key/:item",
+            put(put_api_user_resource_id_path_item),
+        )
 }
@@ -0,0 +1,65 @@
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hyper::Method;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::hue::legacy_api::{HueError, HueResult};
+use crate::server::appstate::AppState;
+
+pub const HUE_APPLICATION_KEY: &str = "hue-application-key";
+
+pub async fn is_known_user(state: &AppState, username: Option<&str>) -> bool {
+    let Some(uuid) = username.and_then(|name| Uuid::parse_str(name).ok()) else {
+        return false;
+    };
+
+    state.res.lock().await.touch_user(&uuid)
+}
+
+/// Middleware for v1 api routes (`/api/:user/..`)
+///
+/// Unknown users are rejected with a hue error type 1 ("unauthorized user"),
+/// except for `GET /api/:user/config`, which (like on a real bridge) is
+/// answered with a reduced config instead.
+pub async fn require_v1_user(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let mut segments = path.trim_start_matches('/').split('/');
+    let username = segments.next();
+
+    if is_known_user(&state, username).await {
+        return next.run(req).await;
+    }
+
+    if req.method() == Method::GET && segments.next() == Some("config") {
+        return next.run(req).await;
+    }
+
+    log::warn!("Rejected v1 request for unknown user {username:?}");
+
+    let address = segments.fold(String::new(), |acc, seg| format!("{acc}/{seg}"));
+    let address = if address.is_empty() { "/" } else { &address };
+
+    Json(vec![HueResult::<()>::Error(HueError::unauthorized_user(
+        address,
+    ))])
+    .into_response()
+}
+
+/// Middleware for v2 api routes, checking the `hue-application-key` header
+pub async fn require_v2_key(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let key = req
+        .headers()
+        .get(HUE_APPLICATION_KEY)
+        .and_then(|value| value.to_str().ok());
+
+    if is_known_user(&state, key).await {
+        return next.run(req).await;
+    }
+
+    log::warn!("Rejected v2 request with unknown application key {key:?}");
+
+    ApiError::Unauthorized.into_response()
+}
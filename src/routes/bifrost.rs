@@ -0,0 +1,55 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{ApiError, ApiResult};
+use crate::routes::auth::{is_known_user, HUE_APPLICATION_KEY};
+use crate::server::appstate::AppState;
+
+#[derive(Debug, Deserialize)]
+struct LinkButtonParams {
+    seconds: Option<u64>,
+}
+
+async fn post_linkbutton(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<LinkButtonParams>,
+) -> ApiResult<Json<Value>> {
+    let key = headers
+        .get(HUE_APPLICATION_KEY)
+        .and_then(|value| value.to_str().ok());
+
+    /* behind a proxy or a container network, any request can look local, so
+     * trusting the source address has to be enabled explicitly */
+    let local = addr.ip().is_loopback() || addr.ip() == state.config().bridge.ipaddress;
+    let trusted = local && state.config().bridge.linkbutton.allow_local;
+
+    if !trusted && !is_known_user(&state, key).await {
+        log::warn!("Rejected link button request from {addr}");
+        return Err(ApiError::Unauthorized);
+    }
+
+    let duration = state
+        .config()
+        .bridge
+        .linkbutton
+        .press_duration(params.seconds);
+
+    state.res.lock().await.press_link_button(duration);
+
+    Ok(Json(json!({
+        "linkbutton": true,
+        "seconds": duration.num_seconds(),
+    })))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/linkbutton", post(post_linkbutton))
+}
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, post, put},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::hue::api::{
+    BehaviorInstance, BehaviorInstanceNew, BehaviorInstanceUpdate, BehaviorScriptType, RType,
+    Resource, V2Reply,
+};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+
+async fn post_behavior_instance(
+    State(state): State<AppState>,
+    Json(req): Json<Value>,
+) -> ApiResult<impl IntoResponse> {
+    log::info!("POST: behavior_instance {}", serde_json::to_string(&req)?);
+
+    let new: BehaviorInstanceNew = serde_json::from_value(req)?;
+
+    if BehaviorScriptType::from_id(&new.script_id).is_none() {
+        return Err(ApiError::NotFound(new.script_id));
+    }
+
+    let link = RType::BehaviorInstance.link_to(Uuid::new_v4());
+
+    log::info!("New behavior instance: {link:?} ({})", new.metadata.name);
+
+    let inst = BehaviorInstance {
+        script_id: new.script_id,
+        enabled: new.enabled,
+        state: json!({}),
+        configuration: new.configuration,
+        dependees: vec![],
+        status: BehaviorInstance::status_for(new.enabled),
+        last_error: String::new(),
+        metadata: new.metadata,
+    };
+
+    state
+        .res
+        .lock()
+        .await
+        .add(&link, Resource::BehaviorInstance(inst))?;
+
+    V2Reply::ok(link)
+}
+
+async fn put_behavior_instance(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT behavior_instance/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::BehaviorInstance.link_to(id);
+    let upd: BehaviorInstanceUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let old: &BehaviorInstance = lock.get(&link)?;
+    let enabled = upd.enabled.unwrap_or(old.enabled);
+
+    /* (re)enabling or reconfiguring starts the routine over */
+    let restart = (enabled && !old.enabled) || upd.configuration.is_some();
+
+    lock.update(&id, |inst: &mut BehaviorInstance| {
+        if let Some(md) = upd.metadata {
+            inst.metadata = md;
+        }
+        if let Some(configuration) = upd.configuration {
+            inst.configuration = configuration;
+        }
+        if restart {
+            inst.state = json!({});
+            inst.last_error.clear();
+        }
+        inst.enabled = enabled;
+        inst.status = BehaviorInstance::status_for(enabled);
+    })?;
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+async fn delete_behavior_instance(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiV2Result {
+    log::info!("DELETE behavior_instance/{id}");
+
+    let link = RType::BehaviorInstance.link_to(id);
+    state.res.lock().await.delete(&link)?;
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(post_behavior_instance))
+        .route("/:id", put(put_behavior_instance))
+        .route("/:id", delete(delete_behavior_instance))
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, State},
+    routing::put,
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::hue::api::{Bridge, BridgeUpdate, RType, V2Reply};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+
+async fn put_bridge(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT bridge/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::Bridge.link_to(id);
+    let upd: BridgeUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    lock.get::<Bridge>(&link)?;
+
+    lock.update(&id, |bridge: &mut Bridge| {
+        if let Some(time_zone) = upd.time_zone {
+            bridge.time_zone = time_zone;
+        }
+    })?;
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/:id", put(put_bridge))
+}
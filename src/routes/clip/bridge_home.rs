@@ -0,0 +1,33 @@
+use axum::{
+    extract::{Path, State},
+    routing::put,
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::hue::api::{BridgeHome, BridgeHomeUpdate, RType, V2Reply};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+
+async fn put_bridge_home(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT bridge_home/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::BridgeHome.link_to(id);
+    let _: BridgeHomeUpdate = serde_json::from_value(put)?;
+
+    /* children and services of the bridge home are maintained by bifrost,
+     * so there is nothing the client can change */
+    state.res.lock().await.get::<BridgeHome>(&link)?;
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/:id", put(put_bridge_home))
+}
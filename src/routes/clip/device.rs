@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, put},
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::hue::api::{
+    Device, DeviceUpdate, Light, RType, Resource, ResourceLink, Room, Scene, V2Reply, Zone,
+};
+use crate::resource::Resources;
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::z2m::request::ClientRequest;
+use crate::z2m::update::DeviceUpdate as Z2mDeviceUpdate;
+
+async fn put_device(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT device/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::Device.link_to(id);
+    let upd: DeviceUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let dev: &Device = lock.get(&link)?;
+    let old_name = dev.metadata.name.clone();
+    let light = dev.light_service().copied();
+
+    if let Some(md) = &upd.metadata {
+        lock.update(&id, |dev: &mut Device| dev.metadata.apply(md))?;
+
+        /* the light service carries a copy of the device metadata */
+        if let Some(light) = &light {
+            lock.update(&light.rid, |light: &mut Light| light.metadata.apply(md))?;
+        }
+
+        if let Some(name) = md.name.as_ref().filter(|name| **name != old_name) {
+            lock.z2m_request(ClientRequest::device_rename(link, name.clone()))?;
+        }
+    }
+
+    if upd.identify.is_some() {
+        if let Some(light) = light {
+            let payload = Z2mDeviceUpdate::default().with_identify();
+            lock.z2m_request(ClientRequest::light_update(light, payload))?;
+        } else {
+            log::warn!("PUT device/{id}: identify is only supported for lights");
+        }
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+/// Remove references to deleted resources from rooms, zones and scenes
+fn unlink(res: &mut Resources, links: &[ResourceLink]) -> ApiResult<()> {
+    for rr in res.get_resources() {
+        match rr.obj {
+            Resource::Room(room) if room.children.iter().any(|rl| links.contains(rl)) => {
+                res.update(&rr.id, |room: &mut Room| {
+                    room.children.retain(|rl| !links.contains(rl));
+                })?;
+            }
+            Resource::Zone(zone) if zone.children.iter().any(|rl| links.contains(rl)) => {
+                res.update(&rr.id, |zone: &mut Zone| {
+                    zone.children.retain(|rl| !links.contains(rl));
+                })?;
+            }
+            Resource::Scene(scene) if scene.actions.iter().any(|a| links.contains(&a.target)) => {
+                res.update(&rr.id, |scene: &mut Scene| {
+                    scene.actions.retain(|act| !links.contains(&act.target));
+                })?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn delete_device(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiV2Result {
+    log::info!("DELETE device/{id}");
+
+    let link = RType::Device.link_to(id);
+    let mut lock = state.res.lock().await;
+    let services = lock.get::<Device>(&link)?.services.clone();
+
+    /* the bridge itself is not a zigbee device */
+    if services
+        .iter()
+        .any(|rl| matches!(rl.rtype, RType::Bridge | RType::BridgeHome))
+    {
+        return Err(ApiError::DeleteDenied(id));
+    }
+
+    lock.z2m_request(ClientRequest::device_remove(link, services.clone()))?;
+
+    let mut links = services;
+    links.push(link);
+    unlink(&mut lock, &links)?;
+
+    for svc in &links {
+        if lock.get_resource(svc.rtype, &svc.rid).is_ok() {
+            lock.delete(svc)?;
+        }
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:id", put(put_device))
+        .route("/:id", delete(delete_device))
+}
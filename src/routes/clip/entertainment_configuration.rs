@@ -0,0 +1,144 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, post, put},
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::ApiResult;
+use crate::hue::api::{
+    Entertainment, EntertainmentConfiguration, EntertainmentConfigurationAction,
+    EntertainmentConfigurationLocations, EntertainmentConfigurationNew,
+    EntertainmentConfigurationStatus, EntertainmentConfigurationType,
+    EntertainmentConfigurationUpdate, RType, Resource, ResourceLink, V2Reply,
+};
+use crate::resource::Resources;
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+
+fn build_config(
+    lock: &Resources,
+    name: String,
+    configuration_type: EntertainmentConfigurationType,
+    locations: EntertainmentConfigurationLocations,
+) -> ApiResult<EntertainmentConfiguration> {
+    let services = locations
+        .service_locations
+        .iter()
+        .map(|loc| Ok((loc.service, lock.get::<Entertainment>(&loc.service)?)))
+        .collect::<ApiResult<Vec<(ResourceLink, &Entertainment)>>>()?;
+
+    Ok(EntertainmentConfiguration::new(
+        name,
+        configuration_type,
+        locations,
+        &services,
+    ))
+}
+
+async fn post_entertainment_configuration(
+    State(state): State<AppState>,
+    Json(req): Json<Value>,
+) -> ApiResult<impl IntoResponse> {
+    log::info!(
+        "POST: entertainment_configuration {}",
+        serde_json::to_string(&req)?
+    );
+
+    let new: EntertainmentConfigurationNew = serde_json::from_value(req)?;
+
+    let link = RType::EntertainmentConfiguration.link_to(Uuid::new_v4());
+
+    log::info!(
+        "New entertainment configuration: {link:?} ({})",
+        new.metadata.name
+    );
+
+    let mut lock = state.res.lock().await;
+    let config = build_config(
+        &lock,
+        new.metadata.name,
+        new.configuration_type,
+        new.locations,
+    )?;
+    lock.add(&link, Resource::EntertainmentConfiguration(config))?;
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+async fn put_entertainment_configuration(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT entertainment_configuration/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::EntertainmentConfiguration.link_to(id);
+    let upd: EntertainmentConfigurationUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let old: &EntertainmentConfiguration = lock.get(&link)?;
+
+    if upd.metadata.is_some() || upd.configuration_type.is_some() || upd.locations.is_some() {
+        let name = upd
+            .metadata
+            .map_or_else(|| old.metadata.name.clone(), |md| md.name);
+        let configuration_type = upd.configuration_type.unwrap_or(old.configuration_type);
+        let locations = upd.locations.unwrap_or_else(|| old.locations.clone());
+        let status = old.status;
+
+        let mut config = build_config(&lock, name, configuration_type, locations)?;
+        config.status = status;
+
+        lock.update(&id, |ent: &mut EntertainmentConfiguration| *ent = config)?;
+    }
+
+    match upd.action {
+        Some(EntertainmentConfigurationAction::Start) => {
+            /* like a real bridge, only one configuration can stream at a time */
+            if let Some(active) = lock.get_active_entertainment() {
+                if active != id {
+                    lock.set_entertainment_status(
+                        &active,
+                        EntertainmentConfigurationStatus::Inactive,
+                    )?;
+                }
+            }
+            lock.set_entertainment_status(&id, EntertainmentConfigurationStatus::Active)?;
+        }
+        Some(EntertainmentConfigurationAction::Stop) => {
+            lock.set_entertainment_status(&id, EntertainmentConfigurationStatus::Inactive)?;
+        }
+        None => {}
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+async fn delete_entertainment_configuration(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiV2Result {
+    log::info!("DELETE entertainment_configuration/{id}");
+
+    let link = RType::EntertainmentConfiguration.link_to(id);
+    let mut lock = state.res.lock().await;
+
+    lock.set_entertainment_status(&id, EntertainmentConfigurationStatus::Inactive)?;
+    lock.delete(&link)?;
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(post_entertainment_configuration))
+        .route("/:id", put(put_entertainment_configuration))
+        .route("/:id", delete(delete_entertainment_configuration))
+}
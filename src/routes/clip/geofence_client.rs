@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Path, State},
+    routing::put,
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::hue::api::{GeofenceClient, GeofenceClientUpdate, RType, V2Reply};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::server::automation;
+
+async fn put_geofence_client(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT geofence_client/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::GeofenceClient.link_to(id);
+    let upd: GeofenceClientUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let was_at_home = lock.get::<GeofenceClient>(&link)?.is_at_home;
+
+    lock.update(&id, |client: &mut GeofenceClient| {
+        if let Some(name) = upd.name {
+            client.name = name;
+        }
+        if upd.is_at_home.is_some() {
+            client.is_at_home = upd.is_at_home;
+        }
+    })?;
+
+    if upd.is_at_home == Some(true) && was_at_home != Some(true) {
+        log::info!("Geofence client {id} arrived home");
+        automation::trigger_coming_home(&mut lock)?;
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/:id", put(put_geofence_client))
+}
@@ -1,15 +1,15 @@
 use axum::{
     extract::{Path, State},
-    routing::put,
+    routing::{delete, put},
     Json, Router,
 };
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::error::ApiError;
 use crate::hue::api::{GroupedLight, GroupedLightUpdate, RType, V2Reply};
-use crate::routes::clip::ApiV2Result;
+use crate::routes::clip::{room, zone, ApiV2Result};
 use crate::server::appstate::AppState;
-use crate::z2m::request::ClientRequest;
 use crate::z2m::update::DeviceUpdate;
 
 async fn put_grouped_light(
@@ -22,25 +22,47 @@ async fn put_grouped_light(
 
     let rlink = RType::GroupedLight.link_to(id);
     let lock = state.res.lock().await;
-    lock.get::<GroupedLight>(&rlink)?;
 
     log::info!("PUT grouped_light/{id}: updating");
 
     let upd: GroupedLightUpdate = serde_json::from_value(put)?;
 
     let payload = DeviceUpdate::default()
+        .with_effect(upd.z2m_effect())
         .with_state(upd.on.map(|on| on.on))
         .with_brightness(upd.dimming.map(|dim| dim.brightness / 100.0 * 254.0))
         .with_color_temp(upd.color_temperature.map(|ct| ct.mirek))
-        .with_color_xy(upd.color.map(|col| col.xy));
+        .with_color_xy(upd.color.map(|col| col.xy))
+        .with_transition(upd.dynamics.and_then(|dynamics| dynamics.transition()));
 
-    lock.z2m_request(ClientRequest::group_update(rlink, payload))?;
+    lock.grouped_light_request(&rlink, payload)?;
 
     drop(lock);
 
     V2Reply::ok(rlink)
 }
 
+/// Grouped lights can't exist on their own, so deleting one deletes the
+/// room or zone that owns it
+async fn delete_grouped_light(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiV2Result {
+    log::info!("DELETE grouped_light/{id}");
+
+    let link = RType::GroupedLight.link_to(id);
+    let mut lock = state.res.lock().await;
+    let owner = lock.get::<GroupedLight>(&link)?.owner;
+
+    match owner.rtype {
+        RType::Room => room::remove_room(&mut lock, &owner)?,
+        RType::Zone => zone::remove_zone(&mut lock, &owner)?,
+        _ => return Err(ApiError::DeleteDenied(id)),
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
 pub fn router() -> Router<AppState> {
-    Router::new().route("/:id", put(put_grouped_light))
+    Router::new()
+        .route("/:id", put(put_grouped_light))
+        .route("/:id", delete(delete_grouped_light))
 }
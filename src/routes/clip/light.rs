@@ -21,17 +21,28 @@ async fn put_light(
     log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
 
     let rlink = RType::Light.link_to(id);
-    let lock = state.res.lock().await;
+    let mut lock = state.res.lock().await;
 
     let _ = lock.get::<Light>(&rlink)?;
 
     let upd: LightUpdate = serde_json::from_value(put)?;
 
+    /* z2m does not report the active effect, so keep track of it here */
+    if let Some(eff) = upd.effects {
+        lock.update::<Light>(&id, |light| {
+            if let Some(effects) = &mut light.effects {
+                effects.status = eff.effect;
+            }
+        })?;
+    }
+
     let payload = DeviceUpdate::default()
+        .with_effect(upd.z2m_effect())
         .with_state(upd.on.map(|on| on.on))
         .with_brightness(upd.dimming.map(|dim| dim.brightness / 100.0 * 254.0))
         .with_color_temp(upd.color_temperature.map(|ct| ct.mirek))
-        .with_color_xy(upd.color.map(|col| col.xy));
+        .with_color_xy(upd.color.map(|col| col.xy))
+        .with_transition(upd.dynamics.and_then(|dynamics| dynamics.transition()));
 
     lock.z2m_request(ClientRequest::light_update(rlink, payload))?;
 
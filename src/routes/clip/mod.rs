@@ -1,7 +1,17 @@
+pub mod behavior_instance;
+pub mod bridge;
+pub mod bridge_home;
+pub mod device;
+pub mod entertainment_configuration;
 pub mod generic;
+pub mod geofence_client;
 pub mod grouped_light;
 pub mod light;
+pub mod room;
 pub mod scene;
+pub mod smart_scene;
+pub mod zigbee_device_discovery;
+pub mod zone;
 
 use axum::{Json, Router};
 use serde::Serialize;
@@ -37,7 +47,23 @@ impl<T: Serialize> V2Reply<T> {
 pub fn router() -> Router<AppState> {
     Router::new()
         .nest("/scene", scene::router())
+        .nest("/smart_scene", smart_scene::router())
+        .nest(
+            "/entertainment_configuration",
+            entertainment_configuration::router(),
+        )
+        .nest("/behavior_instance", behavior_instance::router())
+        .nest("/geofence_client", geofence_client::router())
+        .nest("/device", device::router())
+        .nest("/bridge", bridge::router())
+        .nest("/bridge_home", bridge_home::router())
         .nest("/light", light::router())
         .nest("/grouped_light", grouped_light::router())
+        .nest("/room", room::router())
+        .nest("/zone", zone::router())
+        .nest(
+            "/zigbee_device_discovery",
+            zigbee_device_discovery::router(),
+        )
         .nest("/", generic::router())
 }
@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, post, put},
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::ApiResult;
+use crate::hue::api::{RType, Resource, ResourceLink, Room, RoomUpdate, V2Reply};
+use crate::resource::Resources;
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::z2m::request::ClientRequest;
+
+async fn post_room(
+    State(state): State<AppState>,
+    Json(req): Json<Value>,
+) -> ApiResult<impl IntoResponse> {
+    log::info!("POST: room {}", serde_json::to_string(&req)?);
+
+    let mut room: Room = serde_json::from_value(req)?;
+
+    let link_room = RType::Room.link_to(Uuid::new_v4());
+
+    log::info!("New room: {link_room:?} ({})", room.metadata.name);
+
+    /* the grouped light is added once z2m reports the new group */
+    room.services = vec![];
+
+    let mut lock = state.res.lock().await;
+    lock.add(&link_room, Resource::Room(room))?;
+    lock.z2m_request(ClientRequest::group_add(link_room))?;
+    drop(lock);
+
+    V2Reply::ok(link_room)
+}
+
+async fn put_room(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT room/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let rlink = RType::Room.link_to(id);
+    let upd: RoomUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let room: &Room = lock.get(&rlink)?;
+    let old_name = room.metadata.name.clone();
+    let old_children = room.children.clone();
+
+    if let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) {
+        if *name != old_name {
+            lock.z2m_request(ClientRequest::group_rename(rlink, name.clone()))?;
+        }
+    }
+
+    if let Some(children) = &upd.children {
+        for dev in children.iter().filter(|dev| !old_children.contains(dev)) {
+            lock.z2m_request(ClientRequest::group_member_add(rlink, *dev))?;
+        }
+        for dev in old_children.iter().filter(|dev| !children.contains(dev)) {
+            lock.z2m_request(ClientRequest::group_member_remove(rlink, *dev))?;
+        }
+    }
+
+    lock.update(&id, |room: &mut Room| {
+        if let Some(md) = upd.metadata {
+            room.metadata.apply(md);
+        }
+        if let Some(children) = upd.children {
+            room.children = children;
+        }
+    })?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
+
+/// Delete a room, along with its grouped light and scenes
+pub fn remove_room(res: &mut Resources, link: &ResourceLink) -> ApiResult<()> {
+    let room: &Room = res.get(link)?;
+    let glight = room.grouped_light_service().copied();
+
+    res.z2m_request(ClientRequest::group_remove(*link))?;
+
+    for scene in res.get_scenes_for_room(&link.rid) {
+        res.delete(&RType::Scene.link_to(scene))?;
+    }
+    if let Some(glight) = glight {
+        res.delete(&glight)?;
+    }
+    res.delete(link)
+}
+
+async fn delete_room(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiV2Result {
+    log::info!("DELETE room/{id}");
+
+    let link = RType::Room.link_to(id);
+    remove_room(&mut *state.res.lock().await, &link)?;
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(post_room))
+        .route("/:id", put(put_room))
+        .route("/:id", delete(delete_room))
+}
@@ -8,12 +8,11 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::error::{ApiError, ApiResult};
-use crate::hue::api::{
-    RType, Resource, Scene, SceneStatus, SceneStatusUpdate, SceneUpdate, V2Reply,
-};
+use crate::hue::api::{RType, Resource, Scene, SceneStatusUpdate, SceneUpdate, V2Reply};
 use crate::model::state::AuxData;
 use crate::routes::clip::ApiV2Result;
 use crate::server::appstate::AppState;
+use crate::server::scheduler;
 use crate::z2m::request::ClientRequest;
 
 async fn post_scene(
@@ -39,13 +38,15 @@ async fn post_scene(
             .with_index(sid),
     );
 
-    lock.z2m_request(ClientRequest::scene_store(
-        scene.group,
-        sid,
-        scene.metadata.name.clone(),
-    ))?;
+    /* store the requested actions if given, otherwise the current light states */
+    let request = if scene.actions.is_empty() {
+        ClientRequest::scene_store(scene.group, sid, scene.metadata.name.clone())
+    } else {
+        ClientRequest::scene_add(link_scene)
+    };
 
     lock.add(&link_scene, Resource::Scene(scene))?;
+    lock.z2m_request(request)?;
     drop(lock);
 
     V2Reply::ok(link_scene)
@@ -78,22 +79,16 @@ async fn put_scene(
         })?;
     }
 
-    let scene = lock.get::<Scene>(&rlink)?;
+    if let Some(actions) = upd.actions {
+        lock.update(&id, |scn: &mut Scene| scn.actions = actions)?;
+        lock.z2m_request(ClientRequest::scene_add(rlink))?;
+    }
 
     if let Some(recall) = upd.recall {
         if recall.action == Some(SceneStatusUpdate::Active) {
-            let scenes = lock.get_scenes_for_room(&scene.group.rid);
-            for rid in scenes {
-                lock.update(&rid, |scn: &mut Scene| {
-                    if rid == id {
-                        scn.status = Some(SceneStatus::Static);
-                    } else {
-                        scn.status = Some(SceneStatus::Inactive);
-                    }
-                })?;
-            }
-
-            lock.z2m_request(ClientRequest::scene_recall(rlink))?;
+            let group = lock.get::<Scene>(&rlink)?.group;
+            scheduler::deactivate_smart_scenes(&mut lock, &group)?;
+            lock.recall_scene(&rlink, recall.transition())?;
             drop(lock);
         } else {
             log::error!("Scene recall type not supported: {recall:?}");
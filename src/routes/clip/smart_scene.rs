@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, post, put},
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::ApiResult;
+use crate::hue::api::{
+    RType, Resource, SmartScene, SmartSceneNew, SmartSceneRecallAction, SmartSceneState,
+    SmartSceneUpdate, V2Reply,
+};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::server::scheduler;
+
+async fn post_smart_scene(
+    State(state): State<AppState>,
+    Json(req): Json<Value>,
+) -> ApiResult<impl IntoResponse> {
+    log::info!("POST: smart_scene {}", serde_json::to_string(&req)?);
+
+    let new: SmartSceneNew = serde_json::from_value(req)?;
+
+    let link = RType::SmartScene.link_to(Uuid::new_v4());
+
+    log::info!("New smart scene: {link:?} ({})", new.metadata.name);
+
+    let scene = SmartScene {
+        active_timeslot: None,
+        group: new.group,
+        metadata: new.metadata,
+        state: SmartSceneState::Inactive,
+        transition_duration: new.transition_duration,
+        week_timeslots: new.week_timeslots,
+    };
+
+    let mut lock = state.res.lock().await;
+    lock.add(&link, Resource::SmartScene(scene))?;
+
+    if new.recall.map(|recall| recall.action) == Some(SmartSceneRecallAction::Activate) {
+        scheduler::activate_smart_scene(&mut lock, &link.rid, state.config().bridge.location)?;
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+async fn put_smart_scene(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT smart_scene/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::SmartScene.link_to(id);
+    let upd: SmartSceneUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+
+    lock.update(&id, |scn: &mut SmartScene| {
+        if let Some(md) = upd.metadata {
+            scn.metadata = md;
+        }
+        if let Some(week_timeslots) = upd.week_timeslots {
+            scn.week_timeslots = week_timeslots;
+            /* make the scheduler pick the (possibly new) current timeslot */
+            scn.active_timeslot = None;
+        }
+        if let Some(duration) = upd.transition_duration {
+            scn.transition_duration = duration;
+        }
+    })?;
+
+    match upd.recall.map(|recall| recall.action) {
+        Some(SmartSceneRecallAction::Activate) => {
+            scheduler::activate_smart_scene(&mut lock, &id, state.config().bridge.location)?;
+        }
+        Some(SmartSceneRecallAction::Deactivate) => {
+            scheduler::deactivate_smart_scene(&mut lock, &id)?;
+        }
+        None => {}
+    }
+    drop(lock);
+
+    V2Reply::ok(link)
+}
+
+async fn delete_smart_scene(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiV2Result {
+    log::info!("DELETE smart_scene/{id}");
+
+    let link = RType::SmartScene.link_to(id);
+    state.res.lock().await.delete(&link)?;
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(post_smart_scene))
+        .route("/:id", put(put_smart_scene))
+        .route("/:id", delete(delete_smart_scene))
+}
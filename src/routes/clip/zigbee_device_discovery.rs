@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    routing::put,
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::hue::api::{RType, V2Reply, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryUpdate};
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::z2m::request::ClientRequest;
+
+async fn put_zigbee_device_discovery(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT zigbee_device_discovery/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let link = RType::ZigbeeDeviceDiscovery.link_to(id);
+    let upd: ZigbeeDeviceDiscoveryUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    lock.get::<ZigbeeDeviceDiscovery>(&link)?;
+
+    let Some(action) = upd.action else {
+        return V2Reply::ok(link);
+    };
+
+    if !action.install_codes.is_empty() || !action.search_codes.is_empty() {
+        log::warn!("PUT zigbee_device_discovery/{id}: search and install codes are ignored");
+    }
+
+    let seconds = state.config().bridge.search_duration;
+
+    log::info!("Searching for new devices for {seconds}s");
+
+    lock.z2m_request(ClientRequest::permit_join(seconds))?;
+    lock.start_device_search(chrono::Duration::seconds(
+        i64::try_from(seconds).unwrap_or_default(),
+    ))?;
+    drop(lock);
+
+    /* zigbee2mqtt closes the network by itself, so just report it. A search
+     * started in the meantime extends the deadline, and finishes later. */
+    let res = state.res.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(seconds)).await;
+        let result = res.lock().await.finish_device_search();
+        if let Err(err) = result {
+            log::error!("Failed to finish search for new devices: {err}");
+        }
+    });
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/:id", put(put_zigbee_device_discovery))
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, post, put},
+    Json, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::hue::api::{GroupedLight, RType, Resource, ResourceLink, V2Reply, Zone, ZoneUpdate};
+use crate::resource::Resources;
+use crate::routes::clip::ApiV2Result;
+use crate::server::appstate::AppState;
+use crate::z2m::request::ClientRequest;
+
+async fn post_zone(
+    State(state): State<AppState>,
+    Json(req): Json<Value>,
+) -> ApiResult<impl IntoResponse> {
+    log::info!("POST: zone {}", serde_json::to_string(&req)?);
+
+    let mut zone: Zone = serde_json::from_value(req)?;
+
+    let link_zone = RType::Zone.link_to(Uuid::new_v4());
+    let link_glight = RType::GroupedLight.deterministic(link_zone.rid);
+
+    log::info!("New zone: {link_zone:?} ({})", zone.metadata.name);
+
+    zone.services = vec![link_glight];
+
+    let mut lock = state.res.lock().await;
+    lock.add(&link_zone, Resource::Zone(zone))?;
+    lock.add(
+        &link_glight,
+        Resource::GroupedLight(GroupedLight::new(link_zone)),
+    )?;
+    drop(lock);
+
+    V2Reply::ok(link_zone)
+}
+
+async fn put_zone(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(put): Json<Value>,
+) -> ApiV2Result {
+    log::info!("PUT zone/{id}");
+    log::debug!("json data\n{}", serde_json::to_string_pretty(&put)?);
+
+    let rlink = RType::Zone.link_to(id);
+    let upd: ZoneUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    let zone: &Zone = lock.get(&rlink)?;
+    let old_name = zone.metadata.name.clone();
+    let old_children = zone.children.clone();
+
+    /* zones backed by a z2m group get their name and members from z2m, so
+     * changes are written back, like for rooms */
+    if lock.aux_get(&rlink).is_ok_and(|aux| aux.topic.is_some()) {
+        if let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) {
+            if *name != old_name {
+                lock.z2m_request(ClientRequest::group_rename(rlink, name.clone()))?;
+            }
+        }
+
+        if let Some(children) = &upd.children {
+            for light in children
+                .iter()
+                .filter(|light| !old_children.contains(light))
+            {
+                lock.z2m_request(ClientRequest::group_member_add(rlink, *light))?;
+            }
+            for light in old_children
+                .iter()
+                .filter(|light| !children.contains(light))
+            {
+                lock.z2m_request(ClientRequest::group_member_remove(rlink, *light))?;
+            }
+        }
+    }
+
+    lock.update(&id, |zone: &mut Zone| {
+        if let Some(md) = upd.metadata {
+            zone.metadata.apply(md);
+        }
+        if let Some(children) = upd.children {
+            zone.children = children;
+        }
+    })?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
+
+/// Delete a zone, along with its grouped light and scenes
+pub fn remove_zone(res: &mut Resources, link: &ResourceLink) -> ApiResult<()> {
+    let zone: &Zone = res.get(link)?;
+
+    /* zones backed by a z2m group would just reappear, so they can't be deleted */
+    if res.aux_get(link).is_ok_and(|aux| aux.topic.is_some()) {
+        return Err(ApiError::DeleteDenied(link.rid));
+    }
+
+    let glight = zone.grouped_light_service().copied();
+
+    for scene in res.get_scenes_for_room(&link.rid) {
+        res.delete(&RType::Scene.link_to(scene))?;
+    }
+    if let Some(glight) = glight {
+        res.delete(&glight)?;
+    }
+    res.delete(link)
+}
+
+async fn delete_zone(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiV2Result {
+    log::info!("DELETE zone/{id}");
+
+    let link = RType::Zone.link_to(id);
+    remove_zone(&mut *state.res.lock().await, &link)?;
+
+    V2Reply::ok(link)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(post_zone))
+        .route("/:id", put(put_zone))
+        .route("/:id", delete(delete_zone))
+}
@@ -1,3 +1,4 @@
+use axum::middleware::from_fn_with_state;
 use axum::response::{IntoResponse, Response};
 use axum::{Json, Router};
 use hyper::StatusCode;
@@ -8,6 +9,8 @@ use crate::hue::api::V2Reply;
 use crate::server::appstate::AppState;
 
 pub mod api;
+pub mod auth;
+pub mod bifrost;
 pub mod clip;
 pub mod eventstream;
 pub mod licenses;
@@ -25,8 +28,11 @@ impl IntoResponse for ApiError {
             Self::NotFound(_) | Self::V1NotFound(_) => StatusCode::NOT_FOUND,
             Self::Full(_) => StatusCode::INSUFFICIENT_STORAGE,
             Self::WrongType(_, _) => StatusCode::NOT_ACCEPTABLE,
-            Self::DeleteDenied(_) => StatusCode::FORBIDDEN,
-            Self::V1CreateUnsupported(_) => StatusCode::NOT_IMPLEMENTED,
+            Self::DeleteDenied(_) | Self::Unauthorized => StatusCode::FORBIDDEN,
+            Self::V1CreateUnsupported(_) | Self::V1CommandUnsupported(_, _) => {
+                StatusCode::NOT_IMPLEMENTED
+            }
+            Self::V1InvalidTimePattern(_) | Self::V1InvalidRequest(_) => StatusCode::BAD_REQUEST,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -35,10 +41,20 @@ impl IntoResponse for ApiError {
 }
 
 pub fn router(appstate: AppState) -> Router<()> {
+    let v1_auth = from_fn_with_state(appstate.clone(), auth::require_v1_user);
+    let v2_auth = from_fn_with_state(appstate.clone(), auth::require_v2_key);
+
     Router::new()
-        .nest("/api", api::router())
+        .nest(
+            "/api",
+            api::router().merge(api::user_router().route_layer(v1_auth)),
+        )
         .nest("/licenses", licenses::router())
-        .nest("/clip/v2/resource", clip::router())
-        .nest("/eventstream", eventstream::router())
+        .nest("/bifrost", bifrost::router())
+        .nest(
+            "/clip/v2/resource",
+            clip::router().route_layer(v2_auth.clone()),
+        )
+        .nest("/eventstream", eventstream::router().route_layer(v2_auth))
         .with_state(appstate)
 }
@@ -1,10 +1,8 @@
-use std::collections::HashMap;
 use std::fs::{self, File};
 use std::sync::Arc;
 
 use axum_server::tls_rustls::RustlsConfig;
 use camino::Utf8Path;
-use chrono::Utc;
 use tokio::sync::Mutex;
 
 use crate::config::AppConfig;
@@ -57,6 +55,9 @@ impl AppState {
             res.init(&server::certificate::hue_bridge_id(config.bridge.mac))?;
         }
 
+        res.add_behavior_scripts()?;
+        res.reset_device_discovery()?;
+
         let conf = Arc::new(config);
         let res = Arc::new(Mutex::new(res));
 
@@ -88,21 +89,30 @@ impl AppState {
     }
 
     #[must_use]
-    pub fn api_config(&self, username: &String) -> ApiConfig {
+    pub fn api_config(&self, res: &Resources) -> ApiConfig {
+        let whitelist = res
+            .get_users()
+            .into_iter()
+            .map(|(username, user)| {
+                (
+                    username.to_string(),
+                    Whitelist {
+                        create_date: user.create_date,
+                        last_use_date: user.last_use_date,
+                        name: user.devicetype(),
+                    },
+                )
+            })
+            .collect();
+
         ApiConfig {
             short_config: self.api_short_config(),
             ipaddress: self.conf.bridge.ipaddress,
             netmask: self.conf.bridge.netmask,
             gateway: self.conf.bridge.gateway,
             timezone: self.conf.bridge.timezone.clone(),
-            whitelist: HashMap::from([(
-                username.clone().to_string(),
-                Whitelist {
-                    create_date: Utc::now(),
-                    last_use_date: Utc::now(),
-                    name: "User#foo".to_string(),
-                },
-            )]),
+            linkbutton: res.link_button_pressed(),
+            whitelist,
             ..ApiConfig::default()
         }
     }
@@ -0,0 +1,283 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::error::ApiResult;
+use crate::hue::api::{
+    BehaviorInstance, BehaviorInstanceStatus, BehaviorScriptType, BehaviorWhen, BehaviorWhere,
+    ComingHomeConfig, GoToSleepConfig, RType, TimerConfig, WakeUpConfig, Weekday,
+};
+use crate::resource::Resources;
+use crate::z2m::request::ClientRequest;
+use crate::z2m::update::DeviceUpdate;
+
+/// How often behavior instances are checked for things to do
+const ENGINE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Routines missed by more than this (e.g. while bifrost was not running)
+/// are skipped, rather than started late
+const START_GRACE_MINUTES: i64 = 5;
+
+/// Color temperatures for the "sunrise" wake up style, from warm to neutral
+const SUNRISE_START_MIREK: u32 = 454;
+const SUNRISE_END_MIREK: u32 = 250;
+
+/// Runtime state of a behavior instance, stored in its `state` field
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RoutineState {
+    /// Date of the last run, so routines run at most once per day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_run: Option<NaiveDate>,
+    /// Pending "lights off" (local time)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    off_at: Option<NaiveDateTime>,
+}
+
+fn brightness(percent: f64) -> f64 {
+    percent / 100.0 * 254.0
+}
+
+fn seconds(secs: u32) -> chrono::Duration {
+    chrono::Duration::seconds(i64::from(secs))
+}
+
+/// Send an update to the lights (or whole groups) of a behavior
+fn send_where(res: &Resources, targets: &[BehaviorWhere], payload: &DeviceUpdate) -> ApiResult<()> {
+    for target in targets {
+        match &target.items {
+            Some(items) if !items.is_empty() => {
+                for light in items.iter().filter(|rl| rl.rtype == RType::Light) {
+                    res.z2m_request(ClientRequest::light_update(*light, payload.clone()))?;
+                }
+            }
+            _ => {
+                let glight = res.grouped_light_of(&target.group)?;
+                res.grouped_light_request(&glight, payload.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// If a routine should start now, returns the date it is running for, and
+/// its time point.
+///
+/// The routine starts `lead` before the time point, so a wake up fade
+/// (for example) can start the evening before.
+fn due(
+    when: &BehaviorWhen,
+    lead: chrono::Duration,
+    state: &RoutineState,
+    now: NaiveDateTime,
+) -> Option<(NaiveDate, NaiveDateTime)> {
+    let time = when.time_point.time.as_naive_time()?;
+
+    [now.date(), now.date().succ_opt()?]
+        .into_iter()
+        .filter(|date| state.last_run != Some(*date))
+        .filter(|date| {
+            when.recurrence_days
+                .as_ref()
+                .map_or(true, |days| days.contains(&Weekday::from(date.weekday())))
+        })
+        .map(|date| (date, date.and_time(time)))
+        .find(|(_, point)| {
+            let start = *point - lead;
+            start <= now && now < start + chrono::Duration::minutes(START_GRACE_MINUTES)
+        })
+}
+
+fn run_wake_up(
+    res: &Resources,
+    cfg: &WakeUpConfig,
+    state: &mut RoutineState,
+    now: NaiveDateTime,
+) -> ApiResult<bool> {
+    let fade = seconds(cfg.fade_in_duration.seconds);
+
+    if let Some((date, point)) = due(&cfg.when, fade, state, now) {
+        let sunrise = cfg.style.as_deref() == Some("sunrise");
+
+        /* start from minimal brightness, then fade up until the time point */
+        let start = DeviceUpdate::default()
+            .with_state(Some(true))
+            .with_brightness(Some(1.0))
+            .with_color_temp(sunrise.then_some(SUNRISE_START_MIREK))
+            .with_transition(Some(0.0));
+
+        #[allow(clippy::cast_precision_loss)]
+        let remaining = (point - now).num_seconds().max(0) as f64;
+        let end = DeviceUpdate::default()
+            .with_brightness(Some(brightness(cfg.end_brightness)))
+            .with_color_temp(sunrise.then_some(SUNRISE_END_MIREK))
+            .with_transition(Some(remaining));
+
+        send_where(res, &cfg.where_, &start)?;
+        send_where(res, &cfg.where_, &end)?;
+
+        state.last_run = Some(date);
+        state.off_at = cfg
+            .turn_lights_off_after
+            .map(|after| point + seconds(after.seconds));
+    }
+
+    if state.off_at.is_some_and(|off_at| off_at <= now) {
+        let off = DeviceUpdate::default().with_state(Some(false));
+        send_where(res, &cfg.where_, &off)?;
+        state.off_at = None;
+    }
+
+    /* routines without recurrence only run once */
+    Ok(cfg.when.recurrence_days.is_none() && state.last_run.is_some() && state.off_at.is_none())
+}
+
+fn run_go_to_sleep(
+    res: &Resources,
+    cfg: &GoToSleepConfig,
+    state: &mut RoutineState,
+    now: NaiveDateTime,
+) -> ApiResult<bool> {
+    if let Some((date, _)) = due(&cfg.when, chrono::Duration::zero(), state, now) {
+        let fade = f64::from(cfg.fade_out_duration.seconds);
+
+        let payload = match cfg.end_state.as_deref() {
+            None | Some("turn_off") => DeviceUpdate::default().with_state(Some(false)),
+            Some(_) => DeviceUpdate::default().with_brightness(Some(1.0)),
+        }
+        .with_transition(Some(fade));
+
+        send_where(res, &cfg.where_, &payload)?;
+
+        state.last_run = Some(date);
+    }
+
+    Ok(cfg.when.recurrence_days.is_none() && state.last_run.is_some())
+}
+
+fn run_timer(
+    res: &Resources,
+    cfg: &TimerConfig,
+    state: &mut RoutineState,
+    now: NaiveDateTime,
+) -> ApiResult<bool> {
+    /* timers start counting down when enabled */
+    let Some(off_at) = state.off_at else {
+        state.off_at = Some(now + seconds(cfg.duration.seconds));
+        return Ok(false);
+    };
+
+    if off_at > now {
+        return Ok(false);
+    }
+
+    let off = DeviceUpdate::default().with_state(Some(false));
+    send_where(res, &cfg.where_, &off)?;
+
+    state.off_at = None;
+    state.last_run = Some(now.date());
+
+    Ok(true)
+}
+
+/// Run a single behavior instance, disabling it when it has finished
+fn run_instance(res: &mut Resources, id: &Uuid, now: NaiveDateTime) -> ApiResult<()> {
+    let inst: &BehaviorInstance = res.get(&RType::BehaviorInstance.link_to(*id))?;
+
+    let script = inst.script_type();
+    let config = inst.configuration.clone();
+    let old_state: RoutineState = serde_json::from_value(inst.state.clone()).unwrap_or_default();
+    let mut state = old_state.clone();
+
+    let finished = match script {
+        Some(BehaviorScriptType::WakeUp) => {
+            run_wake_up(res, &serde_json::from_value(config)?, &mut state, now)?
+        }
+        Some(BehaviorScriptType::GoToSleep) => {
+            run_go_to_sleep(res, &serde_json::from_value(config)?, &mut state, now)?
+        }
+        Some(BehaviorScriptType::Timer) => {
+            run_timer(res, &serde_json::from_value(config)?, &mut state, now)?
+        }
+        /* triggered by geofence updates, not by time */
+        Some(BehaviorScriptType::ComingHome) | None => false,
+    };
+
+    if state == old_state && !finished {
+        return Ok(());
+    }
+
+    let state = serde_json::to_value(state)?;
+    res.update(id, |inst: &mut BehaviorInstance| {
+        inst.state = state;
+        if finished {
+            inst.enabled = false;
+            inst.status = BehaviorInstanceStatus::Disabled;
+        }
+    })
+}
+
+fn enabled_instances(res: &Resources, script: Option<BehaviorScriptType>) -> Vec<Uuid> {
+    res.get_resources_by_type(RType::BehaviorInstance)
+        .into_iter()
+        .filter_map(|rr| {
+            let inst: BehaviorInstance = rr.obj.try_into().ok()?;
+            let matches = script.map_or(true, |script| inst.script_type() == Some(script));
+            /* errored instances are retried when reconfigured */
+            let running = inst.enabled && inst.status != BehaviorInstanceStatus::Errored;
+            (running && matches).then_some(rr.id)
+        })
+        .collect()
+}
+
+/// Run all enabled "coming home" behaviors, e.g. when a geofence client
+/// arrives home
+pub fn trigger_coming_home(res: &mut Resources) -> ApiResult<()> {
+    for id in enabled_instances(res, Some(BehaviorScriptType::ComingHome)) {
+        let inst: &BehaviorInstance = res.get(&RType::BehaviorInstance.link_to(id))?;
+        let cfg: ComingHomeConfig = serde_json::from_value(inst.configuration.clone())?;
+
+        log::info!("Running coming home behavior {:?}", inst.metadata.name);
+
+        for what in &cfg.what {
+            match what.recall {
+                Some(scene) if scene.rtype == RType::Scene => res.recall_scene(&scene, None)?,
+                _ => {
+                    let glight = res.grouped_light_of(&what.group)?;
+                    let on = DeviceUpdate::default().with_state(Some(true));
+                    res.grouped_light_request(&glight, on)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute time-based behavior instances (wake up, go to sleep, timers)
+pub async fn behavior_engine(res: Arc<Mutex<Resources>>) -> ApiResult<()> {
+    let mut ticker = interval(ENGINE_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let now = Local::now().naive_local();
+        let mut lock = res.lock().await;
+
+        for id in enabled_instances(&lock, None) {
+            if let Err(err) = run_instance(&mut lock, &id, now) {
+                log::error!("Behavior instance {id} failed: {err}");
+                lock.update(&id, |inst: &mut BehaviorInstance| {
+                    inst.status = BehaviorInstanceStatus::Errored;
+                    inst.last_error = err.to_string();
+                })?;
+            }
+        }
+
+        drop(lock);
+    }
+}
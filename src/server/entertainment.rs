@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use openssl::ssl::{Ssl, SslContext, SslMethod, SslOptions};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::net::UdpSocket;
+use tokio::select;
+use tokio::sync::Mutex;
+use tokio::time::{interval, sleep_until, timeout, Instant, MissedTickBehavior};
+use tokio_openssl::SslStream;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::hue::api::{
+    Entertainment, EntertainmentConfiguration, EntertainmentConfigurationStatus, RType,
+};
+use crate::hue::stream::{HueStreamColorMode, HueStreamPacket, HueStreamTarget};
+use crate::model::types::XY;
+use crate::resource::Resources;
+use crate::z2m::request::ClientRequest;
+use crate::z2m::update::DeviceUpdate;
+
+/// Streaming clients stop the session by simply going quiet
+const SESSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Zigbee networks cannot keep up with the frame rate of streaming clients,
+/// so only one light update is sent per interval (round-robin across lights),
+/// matching the pace at which z2m clients send requests
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+const DTLS_CIPHER: &str = "PSK-AES128-GCM-SHA256";
+const DTLS_MTU: u32 = 1200;
+
+/// Adapter to use a connected [`UdpSocket`] as a datagram stream for openssl
+struct UdpStream(UdpSocket);
+
+impl AsyncRead for UdpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.0.poll_recv(cx, buf)
+    }
+}
+
+impl AsyncWrite for UdpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_send(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Light state requested by the streaming client
+type Target = (XY, f64);
+
+struct Session {
+    config: Uuid,
+    /// Latest requested state, for lights not yet updated
+    pending: BTreeMap<Uuid, Target>,
+    /// Last state sent to each light
+    sent: HashMap<Uuid, Target>,
+    /// Last light updated, for round-robin scheduling
+    cursor: Option<Uuid>,
+}
+
+impl Session {
+    fn new(config: Uuid) -> Self {
+        Self {
+            config,
+            pending: BTreeMap::new(),
+            sent: HashMap::new(),
+            cursor: None,
+        }
+    }
+
+    fn target(mode: HueStreamColorMode, color: [u16; 3]) -> Target {
+        let [a, b, c] = color.map(|v| f64::from(v) / f64::from(u16::MAX));
+        match mode {
+            HueStreamColorMode::Xy => (XY::new(a, b), c),
+            HueStreamColorMode::Rgb => XY::from_rgb(a, b, c),
+        }
+    }
+
+    fn resolve(&self, lock: &Resources, target: HueStreamTarget) -> Option<Uuid> {
+        match target {
+            HueStreamTarget::Light(id) => lock.from_id_v1(u32::from(id)).ok(),
+            HueStreamTarget::Channel(channel) => {
+                let link = RType::EntertainmentConfiguration.link_to(self.config);
+                let config = lock.get::<EntertainmentConfiguration>(&link).ok()?;
+                let service = config.channel_service(channel)?;
+                let ent = lock.get::<Entertainment>(service).ok()?;
+                ent.renderer_reference.map(|light| light.rid)
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, lock: &Resources, pkt: &HueStreamPacket) {
+        for light in &pkt.lights {
+            let Some(id) = self.resolve(lock, light.target) else {
+                continue;
+            };
+
+            let target = Self::target(pkt.color_mode, light.color);
+            if self.sent.get(&id) == Some(&target) {
+                self.pending.remove(&id);
+            } else {
+                self.pending.insert(id, target);
+            }
+        }
+    }
+
+    fn next_update(&mut self) -> Option<(Uuid, Target)> {
+        let id = self
+            .cursor
+            .and_then(|cursor| {
+                self.pending
+                    .range(cursor..)
+                    .map(|(id, _)| *id)
+                    .find(|id| *id != cursor)
+            })
+            .or_else(|| self.pending.keys().next().copied())?;
+
+        let target = self.pending.remove(&id)?;
+        self.sent.insert(id, target);
+        self.cursor = Some(id);
+
+        Some((id, target))
+    }
+}
+
+fn build_context(keys: HashMap<String, Vec<u8>>) -> ApiResult<SslContext> {
+    let mut ctx = SslContext::builder(SslMethod::dtls())?;
+    ctx.set_cipher_list(DTLS_CIPHER)?;
+    ctx.set_options(SslOptions::NO_QUERY_MTU);
+    ctx.set_psk_server_callback(move |_ssl, identity, psk| {
+        let identity = String::from_utf8_lossy(identity.unwrap_or_default());
+        let Some(key) = keys.get(identity.as_ref()) else {
+            log::warn!("Entertainment client with unknown identity {identity:?}");
+            return Ok(0);
+        };
+        let len = key.len().min(psk.len());
+        psk[..len].copy_from_slice(&key[..len]);
+        Ok(len)
+    });
+    Ok(ctx.build())
+}
+
+async fn run_session(res: &Arc<Mutex<Resources>>, socket: UdpSocket) -> ApiResult<()> {
+    let lock = res.lock().await;
+    let Some(config) = lock.get_active_entertainment() else {
+        drop(lock);
+        log::warn!("Rejecting entertainment session: no active entertainment configuration");
+        return Ok(());
+    };
+    let keys = lock
+        .get_users()
+        .into_iter()
+        .map(|(username, user)| (username.to_string(), user.client_key.as_bytes().to_vec()))
+        .collect();
+    drop(lock);
+
+    let ctx = build_context(keys)?;
+    let mut ssl = Ssl::new(&ctx)?;
+    ssl.set_mtu(DTLS_MTU)?;
+
+    let mut stream = SslStream::new(ssl, UdpStream(socket))?;
+    timeout(SESSION_TIMEOUT, Pin::new(&mut stream).accept())
+        .await
+        .map_err(|_| ApiError::HueStreamInvalid("handshake timeout"))??;
+
+    log::info!("Entertainment session started for configuration {config}");
+
+    let mut session = Session::new(config);
+    let mut ticker = interval(UPDATE_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut deadline = Instant::now() + SESSION_TIMEOUT;
+    let mut buf = [0u8; 2048];
+
+    loop {
+        select! {
+            pkt = stream.read(&mut buf) => {
+                let len = match pkt {
+                    Ok(0) => break,
+                    Ok(len) => len,
+                    Err(err) => {
+                        log::warn!("Entertainment stream error: {err}");
+                        break;
+                    }
+                };
+                deadline = Instant::now() + SESSION_TIMEOUT;
+
+                let pkt = match HueStreamPacket::parse(&buf[..len]) {
+                    Ok(pkt) => pkt,
+                    Err(err) => {
+                        log::warn!("Ignoring entertainment packet: {err}");
+                        continue;
+                    }
+                };
+
+                let lock = res.lock().await;
+                if lock.get_active_entertainment() != Some(config) {
+                    log::info!("Entertainment configuration {config} stopped");
+                    break;
+                }
+                session.handle_packet(&lock, &pkt);
+                drop(lock);
+            }
+            () = sleep_until(deadline) => {
+                log::info!("Entertainment session timed out");
+                break;
+            }
+            _ = ticker.tick() => {
+                let Some((id, (xy, bri))) = session.next_update() else {
+                    continue;
+                };
+                let upd = DeviceUpdate::default()
+                    .with_color_xy(Some(xy))
+                    .with_brightness(Some(bri * 254.0))
+                    .with_transition(Some(0.0));
+                res.lock()
+                    .await
+                    .z2m_request(ClientRequest::light_update(RType::Light.link_to(id), upd))?;
+            }
+        }
+    }
+
+    let mut lock = res.lock().await;
+    if lock.get_active_entertainment() == Some(config) {
+        lock.set_entertainment_status(&config, EntertainmentConfigurationStatus::Inactive)?;
+    }
+    drop(lock);
+
+    log::info!("Entertainment session ended");
+
+    Ok(())
+}
+
+/// Serve DTLS streaming sessions from entertainment clients (e.g. Hue Sync).
+///
+/// Like a real bridge, only a single session is served at a time.
+pub async fn entertainment_server(
+    res: Arc<Mutex<Resources>>,
+    listen_addr: Ipv4Addr,
+    listen_port: u16,
+) -> ApiResult<()> {
+    let addr = SocketAddr::from((listen_addr, listen_port));
+    log::info!("entertainment (dtls) listening on {}", addr);
+
+    let mut buf = [0u8; 1];
+
+    loop {
+        let socket = UdpSocket::bind(addr).await?;
+        let (_, peer) = socket.peek_from(&mut buf).await?;
+        socket.connect(peer).await?;
+
+        log::info!("Entertainment client connected from {peer}");
+
+        if let Err(err) = run_session(&res, socket).await {
+            log::error!("Entertainment session from {peer} failed: {err}");
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime, Utc};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tokio::time::interval;
+
+use crate::error::ApiResult;
+use crate::hue::legacy_api::{ApiCommand, ApiRuleCondition, ApiRuleOperator, ApiStatus};
+use crate::hue::timepattern::{self, TimeInterval};
+use crate::resource::Resources;
+use crate::routes::api::{get_groups, get_lights, get_sensors, run_command};
+
+/// How often v1 schedules and rules are evaluated
+const ENGINE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Evaluation state for v1 rules, which trigger on changes
+#[derive(Debug, Default)]
+struct RuleState {
+    /// Last seen value of each address used in a rule condition
+    values: HashMap<String, Value>,
+    /// When the value of each address last changed
+    changed: HashMap<String, NaiveDateTime>,
+    /// Whether the conditions of each rule were met last time
+    active: HashMap<u32, bool>,
+}
+
+/// The v1 view of the bridge state, as addressed by rule conditions
+/// (e.g. "/sensors/2/state/presence")
+fn snapshot(res: &Resources, now: NaiveDateTime) -> ApiResult<Value> {
+    Ok(json!({
+        "config": {
+            "localtime": now.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        },
+        "groups": get_groups(res)?,
+        "lights": get_lights(res)?,
+        "sensors": get_sensors(res)?,
+    }))
+}
+
+fn run_commands(res: &mut Resources, what: &str, commands: &[ApiCommand]) {
+    for cmd in commands {
+        log::debug!("{what}: {:?} {} {}", cmd.method, cmd.address, cmd.body);
+        if let Err(err) = run_command(res, cmd) {
+            log::error!("{what}: command {} failed: {err}", cmd.address);
+        }
+    }
+}
+
+fn run_schedules(res: &mut Resources, last: NaiveDateTime, now: NaiveDateTime) {
+    let schedules = res.legacy().schedules.clone();
+
+    for (id, schedule) in schedules {
+        if schedule.status != ApiStatus::Enabled {
+            continue;
+        }
+
+        let pattern = match schedule.pattern() {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                log::warn!("Schedule {id} ({:?}) is invalid: {err}", schedule.name);
+                continue;
+            }
+        };
+
+        if pattern.fires(schedule.starttime, last, now) {
+            log::info!("Running schedule {id} ({:?})", schedule.name);
+            run_commands(
+                res,
+                &format!("Schedule {id}"),
+                std::slice::from_ref(&schedule.command),
+            );
+        }
+
+        if pattern.is_finished(schedule.starttime, now) {
+            if schedule.autodelete.unwrap_or(true) {
+                log::info!("Schedule {id} finished, deleting");
+                res.legacy_mut().schedules.remove(&id);
+            } else if let Some(schedule) = res.legacy_mut().schedules.get_mut(&id) {
+                schedule.status = ApiStatus::Disabled;
+            }
+        }
+    }
+}
+
+/// Evaluate a single rule condition. Returns whether the condition holds,
+/// and whether it is a change trigger ("dx", "ddx").
+fn evaluate(
+    cond: &ApiRuleCondition,
+    state: &RuleState,
+    last: NaiveDateTime,
+    now: NaiveDateTime,
+) -> (bool, bool) {
+    let current = state.values.get(&cond.address);
+    let changed = state.changed.get(&cond.address).copied();
+    let value = cond.value.as_deref().unwrap_or_default();
+    let period = || timepattern::parse_period(value).ok();
+
+    let number = |val: Option<&Value>| val.and_then(Value::as_f64);
+    let expected = value.parse::<f64>().ok();
+
+    match cond.operator {
+        ApiRuleOperator::Eq => {
+            let holds = current.is_some_and(|val| match val {
+                Value::String(text) => text == value,
+                other => serde_json::from_str::<Value>(value).is_ok_and(|val| val == *other),
+            });
+            (holds, false)
+        }
+        ApiRuleOperator::Gt => (
+            number(current).zip(expected).is_some_and(|(a, b)| a > b),
+            false,
+        ),
+        ApiRuleOperator::Lt => (
+            number(current).zip(expected).is_some_and(|(a, b)| a < b),
+            false,
+        ),
+        ApiRuleOperator::Dx => (changed.is_some_and(|at| last < at && at <= now), true),
+        ApiRuleOperator::Ddx => {
+            let holds = changed.zip(period()).is_some_and(|(at, delay)| {
+                let due = at + delay;
+                last < due && due <= now
+            });
+            (holds, true)
+        }
+        ApiRuleOperator::Stable => (
+            changed.zip(period()).is_some_and(|(at, d)| now - at >= d),
+            false,
+        ),
+        ApiRuleOperator::NotStable => (
+            changed.zip(period()).is_some_and(|(at, d)| now - at < d),
+            false,
+        ),
+        ApiRuleOperator::In | ApiRuleOperator::NotIn => {
+            let inside = value
+                .parse::<TimeInterval>()
+                .is_ok_and(|interval| interval.contains(now));
+            (inside == (cond.operator == ApiRuleOperator::In), false)
+        }
+    }
+}
+
+fn run_rules(
+    res: &mut Resources,
+    state: &mut RuleState,
+    last: NaiveDateTime,
+    now: NaiveDateTime,
+) -> ApiResult<()> {
+    let rules = res.legacy().rules.clone();
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let view = snapshot(res, now)?;
+
+    /* track changes of every address used in a condition */
+    for cond in rules.values().flat_map(|rule| &rule.conditions) {
+        let current = view.pointer(&cond.address).cloned().unwrap_or(Value::Null);
+        match state.values.insert(cond.address.clone(), current.clone()) {
+            Some(old) if old == current => {}
+            Some(_) => {
+                state.changed.insert(cond.address.clone(), now);
+            }
+            /* first sighting is not a change, but starts the clock for "stable" */
+            None => {
+                state.changed.insert(cond.address.clone(), last);
+            }
+        }
+    }
+
+    for (id, rule) in rules {
+        if rule.status != ApiStatus::Enabled || rule.conditions.is_empty() {
+            state.active.remove(&id);
+            continue;
+        }
+
+        let results: Vec<_> = rule
+            .conditions
+            .iter()
+            .map(|cond| evaluate(cond, state, last, now))
+            .collect();
+
+        let holds = results.iter().all(|(holds, _)| *holds);
+        let triggered = results.iter().any(|(_, trigger)| *trigger);
+
+        /* rules fire when their conditions become true, and on every
+         * change when they contain a change trigger */
+        let was_active = state.active.insert(id, holds);
+        let fire = holds && (triggered || was_active == Some(false));
+
+        if !fire {
+            continue;
+        }
+
+        log::info!("Rule {id} ({:?}) triggered", rule.name);
+        run_commands(res, &format!("Rule {id}"), &rule.actions);
+
+        if let Some(rule) = res.legacy_mut().rules.get_mut(&id) {
+            rule.lasttriggered = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+            rule.timestriggered += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute v1 schedules and rules, by sending their embedded commands to
+/// the v1 api handlers
+pub async fn legacy_engine(res: Arc<Mutex<Resources>>) -> ApiResult<()> {
+    let mut ticker = interval(ENGINE_INTERVAL);
+    let mut state = RuleState::default();
+    let mut last = Local::now().naive_local();
+
+    loop {
+        ticker.tick().await;
+
+        let now = Local::now().naive_local();
+        let mut lock = res.lock().await;
+
+        run_schedules(&mut lock, last, now);
+        if let Err(err) = run_rules(&mut lock, &mut state, last, now) {
+            log::error!("Failed to evaluate rules: {err}");
+        }
+
+        drop(lock);
+
+        last = now;
+    }
+}
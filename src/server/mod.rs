@@ -1,6 +1,10 @@
 pub mod appstate;
+pub mod automation;
 pub mod banner;
 pub mod certificate;
+pub mod entertainment;
+pub mod legacy_automation;
+pub mod scheduler;
 
 use std::fs::File;
 use std::io::Write;
@@ -9,9 +13,9 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use axum::body::Body;
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
 use axum::extract::Request;
 use axum::response::Response;
-use axum::routing::IntoMakeService;
 use axum::{Router, ServiceExt};
 use axum_server::service::MakeService;
 use axum_server::tls_rustls::RustlsConfig;
@@ -56,10 +60,12 @@ fn router(appstate: AppState) -> Router<()> {
 }
 
 #[must_use]
-pub fn build_service(appstate: AppState) -> IntoMakeService<NormalizePath<Router>> {
+pub fn build_service(
+    appstate: AppState,
+) -> IntoMakeServiceWithConnectInfo<NormalizePath<Router>, SocketAddr> {
     let normalized = NormalizePathLayer::trim_trailing_slash().layer(router(appstate));
 
-    ServiceExt::<Request>::into_make_service(normalized)
+    ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(normalized)
 }
 
 pub async fn http_server<S>(listen_addr: Ipv4Addr, listen_port: u16, svc: S) -> ApiResult<()>
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use tokio::sync::Mutex;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::config::LocationConfig;
+use crate::error::ApiResult;
+use crate::hue::api::{RType, ResourceLink, SmartScene, SmartSceneState};
+use crate::model::sun;
+use crate::resource::Resources;
+
+/// How often active smart scenes are checked for timeslot changes
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Local sunrise and sunset times for the given date, if a location is known
+fn sun_times(location: Option<LocationConfig>, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+    let loc = location?;
+    let (rise, set) = sun::sunrise_sunset(date, loc.latitude, loc.longitude)?;
+    Some((
+        rise.with_timezone(&Local).time(),
+        set.with_timezone(&Local).time(),
+    ))
+}
+
+/// Recall the scene for the current timeslot of a smart scene, if the
+/// timeslot has changed since last time (or `force` is set)
+fn apply_timeslot(
+    res: &mut Resources,
+    id: &Uuid,
+    location: Option<LocationConfig>,
+    force: bool,
+) -> ApiResult<()> {
+    let scene = res.get::<SmartScene>(&RType::SmartScene.link_to(*id))?;
+
+    let now = Local::now().naive_local();
+    let Some((slot, target)) = scene.timeslot_at(now, |date| sun_times(location, date)) else {
+        return Ok(());
+    };
+
+    if !force && scene.active_timeslot == Some(slot) {
+        return Ok(());
+    }
+
+    log::info!(
+        "Smart scene {:?}: switching to timeslot {slot:?}",
+        scene.metadata.name
+    );
+
+    let transition = f64::from(scene.transition_duration) / 1000.0;
+
+    res.update(id, |scn: &mut SmartScene| scn.active_timeslot = Some(slot))?;
+    res.recall_scene(&target, Some(transition))
+}
+
+/// Activate a smart scene, recalling the scene for the current timeslot
+pub fn activate_smart_scene(
+    res: &mut Resources,
+    id: &Uuid,
+    location: Option<LocationConfig>,
+) -> ApiResult<()> {
+    let group = res
+        .get::<SmartScene>(&RType::SmartScene.link_to(*id))?
+        .group;
+
+    /* only one smart scene can be active for a room or zone */
+    deactivate_smart_scenes(res, &group)?;
+
+    res.update(id, |scn: &mut SmartScene| {
+        scn.state = SmartSceneState::Active;
+    })?;
+    apply_timeslot(res, id, location, true)
+}
+
+pub fn deactivate_smart_scene(res: &mut Resources, id: &Uuid) -> ApiResult<()> {
+    res.update(id, |scn: &mut SmartScene| {
+        scn.state = SmartSceneState::Inactive;
+        scn.active_timeslot = None;
+    })
+}
+
+/// Deactivate all smart scenes for a room or zone (e.g. when a regular
+/// scene is recalled)
+pub fn deactivate_smart_scenes(res: &mut Resources, group: &ResourceLink) -> ApiResult<()> {
+    let active: Vec<Uuid> = res
+        .get_resources_by_type(RType::SmartScene)
+        .into_iter()
+        .filter_map(|rr| {
+            let scene: SmartScene = rr.obj.try_into().ok()?;
+            (scene.group == *group && scene.state == SmartSceneState::Active).then_some(rr.id)
+        })
+        .collect();
+
+    for id in &active {
+        deactivate_smart_scene(res, id)?;
+    }
+
+    Ok(())
+}
+
+/// Switch active smart scenes to the right scene as timeslots begin
+pub async fn smart_scene_scheduler(
+    res: Arc<Mutex<Resources>>,
+    location: Option<LocationConfig>,
+) -> ApiResult<()> {
+    if location.is_none() {
+        log::warn!("No bridge location configured, using fixed times for sunrise/sunset timeslots");
+    }
+
+    let mut ticker = interval(SCHEDULER_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let mut lock = res.lock().await;
+
+        let active: Vec<Uuid> = lock
+            .get_resources_by_type(RType::SmartScene)
+            .into_iter()
+            .filter_map(|rr| {
+                let scene: SmartScene = rr.obj.try_into().ok()?;
+                (scene.state == SmartSceneState::Active).then_some(rr.id)
+            })
+            .collect();
+
+        for id in &active {
+            if let Err(err) = apply_timeslot(&mut lock, id, location, false) {
+                log::error!("Failed to update smart scene {id}: {err}");
+            }
+        }
+
+        drop(lock);
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(clippy::struct_excessive_bools)]
 
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+};
 
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
@@ -49,6 +52,24 @@ pub enum Availability {
     Offline,
 }
 
+/// Payload of `<device>/availability` messages, which is either json
+/// (`{"state": "online"}`), or a plain string with the legacy payload format
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum AvailabilityMessage {
+    State { state: Availability },
+    Legacy(Availability),
+}
+
+impl AvailabilityMessage {
+    #[must_use]
+    pub const fn availability(&self) -> Availability {
+        match self {
+            Self::State { state } | Self::Legacy(state) => *state,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Hash)]
 #[serde(transparent)]
 pub struct IeeeAddress(#[serde(deserialize_with = "ieee_address")] u64);
@@ -59,6 +80,27 @@ impl Debug for IeeeAddress {
     }
 }
 
+/// Formatted the way zigbee2mqtt does ("0x001788010b2c3d4e")
+impl Display for IeeeAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:016x}", self.0)
+    }
+}
+
+impl IeeeAddress {
+    /// The address formatted like a mac address ("00:17:88:01:0b:2c:3d:4e"),
+    /// as used by the hue api
+    #[must_use]
+    pub fn mac_address(&self) -> String {
+        self.0
+            .to_be_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
 fn ieee_address<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: Deserializer<'de>,
@@ -73,6 +115,7 @@ where
 #[serde(rename_all = "lowercase")]
 pub enum BridgeOnlineState {
     Online,
+    Offline,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -91,6 +134,15 @@ pub struct BridgeEvent {
     pub event_type: String,
 }
 
+/// Device data of `device_joined` and `device_interview` bridge events
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BridgeEventDevice {
+    pub friendly_name: String,
+    pub ieee_address: IeeeAddress,
+    /// Interview status ("started", "successful", "failed")
+    pub status: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct BridgeLogging {
@@ -347,16 +399,61 @@ impl Device {
         })
     }
 
+    /// The switch of an on/off-only device (smart plug, relay), if it has a
+    /// single one
     #[must_use]
-    pub fn expose_action(&self) -> bool {
-        self.exposes().iter().any(|exp| {
-            if let Expose::Enum(ExposeEnum { name, .. }) = exp {
-                name == "action"
-            } else {
-                false
-            }
+    pub fn expose_plug(&self) -> Option<&ExposeSwitch> {
+        self.exposes().iter().find_map(|exp| match exp {
+            Expose::Switch(switch) if switch.feature_binary("state").is_some() => Some(switch),
+            _ => None,
         })
     }
+
+    #[must_use]
+    pub fn expose_binary(&self, name: &str) -> Option<&ExposeBinary> {
+        self.exposes().iter().find_map(|exp| match exp {
+            Expose::Binary(bin) if bin.name == name => Some(bin),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn expose_numeric(&self, name: &str) -> Option<&ExposeNumeric> {
+        self.exposes().iter().find_map(|exp| match exp {
+            Expose::Numeric(num) if num.name == name => Some(num),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn expose_enum(&self, name: &str) -> Option<&Expose> {
+        self.exposes()
+            .iter()
+            .find(|exp| matches!(exp, Expose::Enum(num) if num.name == name))
+    }
+
+    /// True if the device reports occupancy, illuminance or temperature
+    #[must_use]
+    pub fn expose_sensor(&self) -> bool {
+        self.expose_binary("occupancy").is_some()
+            || self.expose_numeric("illuminance_lux").is_some()
+            || self.expose_numeric("illuminance").is_some()
+            || self.expose_numeric("temperature").is_some()
+    }
+
+    /// All possible values of the "action" expose (empty if there is none)
+    #[must_use]
+    pub fn expose_action_values(&self) -> &[String] {
+        self.exposes()
+            .iter()
+            .find_map(|exp| match exp {
+                Expose::Enum(ExposeEnum { name, values, .. }) if name == "action" => {
+                    Some(values.as_slice())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -511,11 +608,35 @@ pub struct ExposeSwitch {
     pub features: Vec<Expose>,
 }
 
+impl ExposeSwitch {
+    /// Binary feature by property name. Devices with several switches use
+    /// per-endpoint properties (`state_l1`), so this only finds plain ones.
+    #[must_use]
+    pub fn feature_binary(&self, property: &str) -> Option<&ExposeBinary> {
+        self.features.iter().find_map(|exp| match exp {
+            Expose::Binary(bin) if bin.property == property => Some(bin),
+            _ => None,
+        })
+    }
+}
+
+/// On/off-only devices are presented as lights without any features
+impl From<&ExposeSwitch> for ExposeLight {
+    fn from(switch: &ExposeSwitch) -> Self {
+        Self {
+            features: switch.features.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Endpoint {
     pub bindings: Vec<Binding>,
     pub clusters: Clusters,
     pub configured_reportings: Vec<ConfiguredReporting>,
+    /// Scenes stored on this endpoint. Zigbee2mqtt only reports their id
+    /// and name, not the light state they recall, so they are kept as-is.
+    #[serde(default)]
     pub scenes: Vec<Value>,
 }
 
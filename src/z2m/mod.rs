@@ -1,39 +1,44 @@
 pub mod api;
 pub mod request;
+pub mod transport;
 pub mod update;
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use chrono::{DateTime, Duration, Utc};
-use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
 use serde_json::{json, Value};
-use tokio::net::TcpStream;
 use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::Receiver;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite, MaybeTlsStream, WebSocketStream};
 use uuid::Uuid;
 
 use crate::config::{AppConfig, Z2mServer};
 use crate::hue;
 use crate::hue::api::{
-    Button, ButtonData, ButtonMetadata, ButtonReport, ColorTemperature, ColorTemperatureUpdate,
-    ColorUpdate, Device, DeviceArchetype, DeviceProductData, Dimming, DimmingUpdate, GroupedLight,
-    Light, LightColor, LightUpdate, Metadata, RType, Resource, ResourceLink, Room, RoomArchetype,
-    RoomMetadata, Scene, SceneAction, SceneActionElement, SceneMetadata, SceneStatus,
-    ZigbeeConnectivity, ZigbeeConnectivityStatus,
+    Button, ButtonData, ButtonEvent, ButtonMetadata, ButtonReport, ColorGamut, ColorTemperature,
+    ColorTemperatureUpdate, ColorUpdate, Device, DeviceArchetype, DeviceProductData, Dimming,
+    DimmingUpdate, Entertainment, GroupedLight, Light, LightAlert, LightColor, LightEffects,
+    LightLevel, LightSignaling, LightUpdate, Metadata, MirekSchema, Motion, RType, Resource,
+    ResourceLink, Room, RoomArchetype, RoomMetadata, Scene, SceneAction, SceneActionElement,
+    SceneMetadata, SceneStatus, Temperature, ZigbeeConnectivity, ZigbeeConnectivityStatus, Zone,
 };
 
 use crate::error::{ApiError, ApiResult};
 use crate::hue::scene_icons;
 use crate::model::state::AuxData;
+use crate::model::types::XY;
 use crate::resource::Resources;
-use crate::z2m::api::{ExposeLight, Message, RawMessage};
+use crate::z2m::api::{
+    Availability, AvailabilityMessage, BridgeEvent, BridgeEventDevice, BridgeInfo,
+    BridgeOnlineState, Expose, ExposeLight, Message, RawMessage,
+};
 use crate::z2m::request::{ClientRequest, Z2mRequest};
-use crate::z2m::update::{DeviceColor, DeviceUpdate};
+use crate::z2m::transport::Transport;
+use crate::z2m::update::DeviceUpdate;
 
 #[derive(Debug)]
 struct LearnScene {
@@ -42,6 +47,27 @@ struct LearnScene {
     pub known: HashMap<Uuid, SceneAction>,
 }
 
+/// What a z2m action value does to the buttons of a device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ButtonAction {
+    /// Event on the button with this (0-based) index
+    Event(usize, ButtonEvent),
+    /// End of a `<name>_move_<direction>` action, on whichever button is held
+    Stop,
+}
+
+/// Map from z2m action values to what they do
+type ButtonActions = HashMap<String, ButtonAction>;
+
+/// Buttons of a device, and the mapping from z2m action values to them
+#[derive(Debug)]
+struct DeviceButtons {
+    links: Vec<Uuid>,
+    actions: ButtonActions,
+    /// Button held by the last move action, until it is stopped
+    held: Option<usize>,
+}
+
 pub struct Client {
     name: String,
     server: Z2mServer,
@@ -51,6 +77,11 @@ pub struct Client {
     rmap: HashMap<Uuid, String>,
     learn: HashMap<Uuid, LearnScene>,
     ignore: HashSet<String>,
+    buttons: HashMap<Uuid, DeviceButtons>,
+    hs_lights: HashSet<Uuid>,
+    ct_lights: HashSet<Uuid>,
+    effects: HashMap<Uuid, Vec<String>>,
+    identify_lights: HashSet<Uuid>,
 }
 
 impl Client {
@@ -64,6 +95,11 @@ impl Client {
         let rmap = HashMap::new();
         let learn = HashMap::new();
         let ignore = HashSet::new();
+        let buttons = HashMap::new();
+        let hs_lights = HashSet::new();
+        let ct_lights = HashSet::new();
+        let effects = HashMap::new();
+        let identify_lights = HashSet::new();
         Ok(Self {
             name,
             server,
@@ -73,6 +109,11 @@ impl Client {
             rmap,
             learn,
             ignore,
+            buttons,
+            hs_lights,
+            ct_lights,
+            effects,
+            identify_lights,
         })
     }
 
@@ -81,18 +122,46 @@ impl Client {
 
         let link_device = RType::Device.deterministic(&dev.ieee_address);
         let link_light = RType::Light.deterministic(&dev.ieee_address);
+        let link_ent = RType::Entertainment.deterministic(&dev.ieee_address);
+        let link_zbc = RType::ZigbeeConnectivity.deterministic(&dev.ieee_address);
 
         let product_data = DeviceProductData::guess_from_device(dev);
-        let metadata = Metadata::new(DeviceArchetype::SpotBulb, name);
+        let archetype = self.archetype_override(dev);
+        let metadata = Metadata::new(
+            archetype
+                .clone()
+                .unwrap_or_else(|| product_data.product_archetype.clone()),
+            name,
+        );
+        let effect = dev.expose_enum("effect");
+        let identify = dev.expose_enum("identify").is_some();
+        let devconf = self.config.device(name, &dev.ieee_address.to_string());
+        let (gamut_type, gamut) = devconf
+            .and_then(|conf| conf.gamut.as_ref()?.resolve())
+            .unwrap_or_else(|| ColorGamut::guess_from_device(dev));
+        let emulate_ct = devconf.is_some_and(|conf| conf.emulate_ct);
+        let zbc = ZigbeeConnectivity::new(link_device, dev.ieee_address.mac_address());
+
+        /* like on real hue plugs, on/off-only lights can't be used for
+         * entertainment */
+        let renderer = ["brightness", "color_xy", "color_hs"]
+            .iter()
+            .any(|feature| expose.feature(feature).is_some());
+        let services = if renderer {
+            vec![link_light, link_ent, link_zbc]
+        } else {
+            vec![link_light, link_zbc]
+        };
 
         let dev = hue::api::Device {
-            product_data,
+            product_data: product_data.clone(),
             metadata: metadata.clone(),
-            services: vec![link_light],
+            services: services.clone(),
         };
 
         self.map.insert(name.to_string(), link_light.rid);
         self.rmap.insert(link_light.rid, name.to_string());
+        self.set_light_effects(link_light.rid, effect, identify);
 
         let mut res = self.state.lock().await;
         let mut light = Light::new(link_device, metadata);
@@ -107,63 +176,302 @@ impl Client {
             .and_then(ColorTemperature::extract_from_expose);
         log::trace!("Detected color temperature: {:?}", &light.color_temperature);
 
-        light.color = expose
-            .feature("color_xy")
-            .and_then(LightColor::extract_from_expose);
+        /* lights without xy support are controlled by hue/saturation */
+        let color_hs = expose.feature("color_hs");
+        let color_xy = expose.feature("color_xy");
+        if color_xy.is_none() && color_hs.is_some() {
+            self.hs_lights.insert(link_light.rid);
+        } else {
+            self.hs_lights.remove(&link_light.rid);
+        }
+
+        light.color = color_xy
+            .or(color_hs)
+            .and_then(LightColor::extract_from_expose)
+            .map(|color| color.with_gamut(gamut_type, gamut));
         log::trace!("Detected color: {:?}", &light.color);
 
+        if emulate_ct && light.color.is_some() && light.color_temperature.is_none() {
+            log::debug!("[{}] Emulating color temperature for {name}", self.name);
+            light.color_temperature = Some(ColorTemperature::emulated());
+            self.ct_lights.insert(link_light.rid);
+        } else {
+            self.ct_lights.remove(&link_light.rid);
+        }
+
+        /* alerts, signals and effects are all triggered through the z2m "effect" */
+        if let Some(effect) = effect {
+            light.alert = LightAlert::extract_from_expose(effect);
+            light.signaling = LightSignaling::extract_from_expose(effect);
+            light.effects = LightEffects::extract_from_expose(effect);
+        }
+        log::trace!("Detected effects: {:?}", &light.effects);
+
+        /* devices known from before entertainment and connectivity support
+         * need the new services */
+        Self::add_missing_services(&mut res, &link_device, &services)?;
+        if !renderer {
+            Self::remove_service(&mut res, &link_device, &link_ent)?;
+        }
+        Self::refresh_known_device(&mut res, &link_device, &product_data, archetype.as_ref())?;
+        Self::refresh_known_light(&mut res, &link_light, &light, archetype.as_ref())?;
+
         res.aux_set(&link_light, AuxData::new().with_topic(name));
         res.add(&link_device, Resource::Device(dev))?;
         res.add(&link_light, Resource::Light(light))?;
+        if renderer {
+            res.add(
+                &link_ent,
+                Resource::Entertainment(Entertainment::for_light(link_device, link_light)),
+            )?;
+        }
+        res.add(&link_zbc, Resource::ZigbeeConnectivity(zbc))?;
         drop(res);
 
         Ok(())
     }
 
-    pub async fn add_switch(&mut self, dev: &api::Device) -> ApiResult<()> {
-        let name = &dev.friendly_name;
+    /// Remember the effects a light supports, and whether it can identify
+    /// itself, for [`Self::adapt_light_update`]
+    fn set_light_effects(&mut self, light: Uuid, effect: Option<&Expose>, identify: bool) {
+        if let Some(Expose::Enum(effect)) = effect {
+            self.effects.insert(light, effect.values.clone());
+        } else {
+            self.effects.remove(&light);
+        }
 
-        let link_device = RType::Device.deterministic(&dev.ieee_address);
-        let link_button = RType::Button.deterministic(&dev.ieee_address);
-        let link_zbc = RType::ZigbeeConnectivity.deterministic(&dev.ieee_address);
+        if identify {
+            self.identify_lights.insert(light);
+        } else {
+            self.identify_lights.remove(&light);
+        }
+    }
 
-        let dev = hue::api::Device {
-            product_data: DeviceProductData::guess_from_device(dev),
-            metadata: Metadata::new(DeviceArchetype::UnknownArchetype, "foo"),
-            services: vec![link_button, link_zbc],
+    /// Add services to a device that is already known, since adding the
+    /// device again leaves it unchanged
+    fn add_missing_services(
+        res: &mut Resources,
+        device: &ResourceLink,
+        services: &[ResourceLink],
+    ) -> ApiResult<()> {
+        let Ok(known) = res.get::<Device>(device) else {
+            return Ok(());
         };
 
-        self.map.insert(name.to_string(), link_button.rid);
-        self.rmap.insert(link_button.rid, name.to_string());
+        if services.iter().all(|svc| known.services.contains(svc)) {
+            return Ok(());
+        }
 
-        let mut res = self.state.lock().await;
-        let button = Button {
-            owner: link_device,
-            metadata: ButtonMetadata { control_id: 0 },
-            button: ButtonData {
-                button_report: Some(ButtonReport {
-                    updated: Utc::now(),
-                    event: String::from("initial_press"),
-                }),
-                repeat_interval: Some(100),
-                event_values: Some(json!(["initial_press", "repeat"])),
-            },
+        let missing: Vec<_> = services
+            .iter()
+            .filter(|svc| !known.services.contains(svc))
+            .copied()
+            .collect();
+
+        res.update::<Device>(&device.rid, |known| known.services.extend(missing))
+    }
+
+    /// Remove a service from a device that is already known, if present
+    fn remove_service(
+        res: &mut Resources,
+        device: &ResourceLink,
+        service: &ResourceLink,
+    ) -> ApiResult<()> {
+        let Ok(known) = res.get::<Device>(device) else {
+            return Ok(());
         };
 
-        let zbc = ZigbeeConnectivity {
-            owner: link_device,
-            mac_address: String::from("11:22:33:44:55:66:77:89"),
-            status: ZigbeeConnectivityStatus::ConnectivityIssue,
-            channel: Some(json!({
-                "status": "set",
-                "value": "channel_25",
-            })),
-            extended_pan_id: String::from("0123456789abcdef"),
+        if !known.services.contains(service) {
+            return Ok(());
+        }
+
+        res.update::<Device>(&device.rid, |known| {
+            known.services.retain(|svc| svc != service);
+        })?;
+
+        if res.get_resource_by_id(&service.rid).is_ok() {
+            res.delete(service)?;
+        }
+
+        Ok(())
+    }
+
+    /// Archetype configured for this device, if any
+    fn archetype_override(&self, dev: &api::Device) -> Option<DeviceArchetype> {
+        self.config
+            .device(&dev.friendly_name, &dev.ieee_address.to_string())
+            .and_then(|conf| conf.archetype.clone())
+    }
+
+    /// Keep devices that are already known up to date. The product data
+    /// always follows zigbee2mqtt, but the archetype can be changed from the
+    /// Hue app, so it is only changed if configured.
+    fn refresh_known_device(
+        res: &mut Resources,
+        device: &ResourceLink,
+        product_data: &DeviceProductData,
+        archetype: Option<&DeviceArchetype>,
+    ) -> ApiResult<()> {
+        let Ok(known) = res.get::<Device>(device) else {
+            return Ok(());
         };
 
-        res.add(&link_device, Resource::Device(dev))?;
-        res.add(&link_button, Resource::Button(button))?;
-        res.add(&link_zbc, Resource::ZigbeeConnectivity(zbc))?;
+        if known.product_data == *product_data
+            && archetype.map_or(true, |arch| known.metadata.archetype == *arch)
+        {
+            return Ok(());
+        }
+
+        res.update::<Device>(&device.rid, |known| {
+            known.product_data = product_data.clone();
+            if let Some(archetype) = archetype {
+                known.metadata.archetype = archetype.clone();
+            }
+        })
+    }
+
+    /// Keep lights that are already known up to date. Known lights keep
+    /// their state, but the configured archetype, the gamut and color
+    /// temperature emulation might have changed.
+    fn refresh_known_light(
+        res: &mut Resources,
+        link: &ResourceLink,
+        light: &Light,
+        archetype: Option<&DeviceArchetype>,
+    ) -> ApiResult<()> {
+        let Ok(known) = res.get::<Light>(link) else {
+            return Ok(());
+        };
+
+        let archetype = archetype.filter(|arch| known.metadata.archetype != **arch);
+        let gamut_changed = light.color.as_ref().is_some_and(|color| {
+            known.color.as_ref().map(|col| (&col.gamut, col.gamut_type))
+                != Some((&color.gamut, color.gamut_type))
+        });
+        let ct_changed = known.color_temperature.is_some() != light.color_temperature.is_some();
+
+        if archetype.is_none() && !gamut_changed && !ct_changed {
+            return Ok(());
+        }
+
+        res.update::<Light>(&link.rid, |known| {
+            if let Some(archetype) = archetype {
+                known.metadata.archetype = archetype.clone();
+            }
+            if let (Some(color), Some(known_color)) = (&light.color, &mut known.color) {
+                known_color.gamut.clone_from(&color.gamut);
+                known_color.gamut_type = color.gamut_type;
+            }
+            if ct_changed {
+                known.color_temperature.clone_from(&light.color_temperature);
+            }
+        })
+    }
+
+    /// Sensor services (motion, light level, temperature) exposed by this device
+    fn sensor_services(dev: &api::Device, owner: ResourceLink) -> Vec<(ResourceLink, Resource)> {
+        let mut services = vec![];
+
+        if dev.expose_binary("occupancy").is_some() {
+            let link = RType::Motion.deterministic(&dev.ieee_address);
+            services.push((link, Resource::Motion(Motion::new(owner))));
+        }
+
+        if dev.expose_numeric("illuminance_lux").is_some()
+            || dev.expose_numeric("illuminance").is_some()
+        {
+            let link = RType::LightLevel.deterministic(&dev.ieee_address);
+            services.push((link, Resource::LightLevel(LightLevel::new(owner))));
+        }
+
+        if dev.expose_numeric("temperature").is_some() {
+            let link = RType::Temperature.deterministic(&dev.ieee_address);
+            services.push((link, Resource::Temperature(Temperature::new(owner))));
+        }
+
+        services
+    }
+
+    /// Button services for each physical button found in the "action" expose
+    /// of this device, along with the mapping from z2m action values to
+    /// button events.
+    fn button_services(
+        dev: &api::Device,
+        owner: ResourceLink,
+    ) -> (Vec<(ResourceLink, Resource)>, DeviceButtons) {
+        let (buttons, actions) = parse_actions(dev.expose_action_values());
+
+        let mut services = vec![];
+        let mut links = vec![];
+
+        for (idx, (_name, event_values)) in buttons.into_iter().enumerate() {
+            let control_id = u32::try_from(idx + 1).unwrap_or(u32::MAX);
+            /* button uuids are derived from their (1-based) position */
+            let link = RType::Button.deterministic((&dev.ieee_address, idx + 1));
+
+            let button = Button {
+                owner,
+                metadata: ButtonMetadata { control_id },
+                button: ButtonData {
+                    button_report: None,
+                    repeat_interval: event_values.contains(&ButtonEvent::Repeat).then_some(800),
+                    event_values: Some(event_values),
+                },
+            };
+
+            links.push(link.rid);
+            services.push((link, Resource::Button(button)));
+        }
+
+        let buttons = DeviceButtons {
+            links,
+            actions,
+            held: None,
+        };
+
+        (services, buttons)
+    }
+
+    pub async fn add_device(&mut self, dev: &api::Device) -> ApiResult<()> {
+        let name = &dev.friendly_name;
+
+        let link_device = RType::Device.deterministic(&dev.ieee_address);
+
+        let mut services = Self::sensor_services(dev, link_device);
+        let (buttons, actions) = Self::button_services(dev, link_device);
+        services.extend(buttons);
+
+        let link_zbc = RType::ZigbeeConnectivity.deterministic(&dev.ieee_address);
+        let zbc = ZigbeeConnectivity::new(link_device, dev.ieee_address.mac_address());
+        services.push((link_zbc, Resource::ZigbeeConnectivity(zbc)));
+
+        let product_data = DeviceProductData::guess_from_device(dev);
+        let archetype = self.archetype_override(dev);
+
+        let device = hue::api::Device {
+            product_data: product_data.clone(),
+            metadata: Metadata::new(
+                archetype
+                    .clone()
+                    .unwrap_or(DeviceArchetype::UnknownArchetype),
+                name,
+            ),
+            services: services.iter().map(|(link, _)| *link).collect(),
+        };
+
+        /* updates for all services arrive on a single topic, so map it to the device */
+        self.map.insert(name.clone(), link_device.rid);
+        self.rmap.insert(link_device.rid, name.clone());
+        self.buttons.insert(link_device.rid, actions);
+
+        let mut res = self.state.lock().await;
+        Self::add_missing_services(&mut res, &link_device, &[link_zbc])?;
+        Self::refresh_known_device(&mut res, &link_device, &product_data, archetype.as_ref())?;
+        res.aux_set(&link_device, AuxData::new().with_topic(name));
+        res.add(&link_device, Resource::Device(device))?;
+        for (link, obj) in services {
+            res.add(&link, obj)?;
+        }
         drop(res);
 
         Ok(())
@@ -188,21 +496,41 @@ impl Client {
             room_name = &grp.friendly_name;
         }
 
-        let link_room = RType::Room.deterministic(&grp.friendly_name);
-        let link_glight = RType::GroupedLight.deterministic((link_room.rid, grp.id));
+        /* groups can be configured to show up as zones, which may overlap */
+        let is_zone = self
+            .config
+            .rooms
+            .get(&grp.friendly_name)
+            .is_some_and(|conf| conf.zone);
+
+        let (group_rtype, child_rtype) = if is_zone {
+            (RType::Zone, RType::Light)
+        } else {
+            (RType::Room, RType::Device)
+        };
 
         let children = grp
             .members
             .iter()
-            .map(|f| RType::Device.deterministic(&f.ieee_address))
+            .map(|f| child_rtype.deterministic(&f.ieee_address))
             .collect();
 
         let topic = grp.friendly_name.to_string();
 
         let mut res = self.state.lock().await;
 
+        /* groups renamed through bifrost keep their original uuid */
+        let link_room = res
+            .find_by_topic(group_rtype, &topic)
+            .unwrap_or_else(|| group_rtype.deterministic(&grp.friendly_name));
+        let link_glight = RType::GroupedLight.deterministic((link_room.rid, grp.id));
+
         let mut scenes_new = HashSet::new();
 
+        /* zigbee2mqtt does not expose the state stored in a scene, so
+         * scenes created outside bifrost start without actions, until they
+         * are learned on the first recall (see learn_scene_recall). Scenes
+         * written by bifrost are already known, and keep their actions. */
         for scn in &grp.scenes {
             let scene = Scene {
                 actions: vec![],
@@ -234,11 +562,13 @@ impl Client {
             res.add(&link_scene, Resource::Scene(scene))?;
         }
 
-        if let Ok(room) = res.get::<Room>(&link_room) {
+        let known = res.get_resource(group_rtype, &link_room.rid).is_ok();
+
+        if known {
             log::info!(
                 "[{}] {link_room:?} ({}) known, updating..",
                 self.name,
-                room.metadata.name
+                room_name
             );
 
             let scenes_old: HashSet<Uuid> =
@@ -263,27 +593,67 @@ impl Client {
             );
         }
 
-        let mut metadata = RoomMetadata::new(RoomArchetype::Home, room_name);
-        if let Some(room_conf) = self.config.rooms.get(&topic) {
-            if let Some(name) = &room_conf.name {
-                metadata.name = name.to_string();
-            }
-            if let Some(icon) = &room_conf.icon {
-                metadata.archetype = *icon;
-            }
-        };
+        let room_conf = self.config.rooms.get(&topic);
+        let icon = room_conf.and_then(|conf| conf.icon);
 
-        let room = Room {
-            children,
-            metadata,
-            services: vec![link_glight],
-        };
+        let mut metadata = RoomMetadata::new(RoomArchetype::Home, room_name);
+        if let Some(name) = room_conf.and_then(|conf| conf.name.as_ref()) {
+            metadata.name.clone_from(name);
+        }
+        if let Some(icon) = icon {
+            metadata.archetype = icon;
+        }
 
         self.map.insert(topic.clone(), link_glight.rid);
         self.rmap.insert(link_glight.rid, topic.clone());
         self.rmap.insert(link_room.rid, topic.clone());
 
-        res.add(&link_room, Resource::Room(room))?;
+        res.aux_set(
+            &link_room,
+            AuxData::new().with_topic(&topic).with_index(grp.id),
+        );
+
+        if is_zone && known {
+            /* membership and name are owned by z2m, archetype by the hue app */
+            res.update::<Zone>(&link_room.rid, |zone| {
+                zone.children = children;
+                zone.metadata.name = metadata.name;
+                if let Some(icon) = icon {
+                    zone.metadata.archetype = icon;
+                }
+                if zone.grouped_light_service().is_none() {
+                    zone.services.push(link_glight);
+                }
+            })?;
+        } else if is_zone {
+            let zone = Zone {
+                children,
+                metadata,
+                services: vec![link_glight],
+            };
+
+            res.add(&link_room, Resource::Zone(zone))?;
+        } else if known {
+            /* membership and name are owned by z2m, archetype by the hue app */
+            res.update::<Room>(&link_room.rid, |room| {
+                room.children = children;
+                room.metadata.name = metadata.name;
+                if let Some(icon) = icon {
+                    room.metadata.archetype = icon;
+                }
+                if room.grouped_light_service().is_none() {
+                    room.services.push(link_glight);
+                }
+            })?;
+        } else {
+            let room = Room {
+                children,
+                metadata,
+                services: vec![link_glight],
+            };
+
+            res.add(&link_room, Resource::Room(room))?;
+        }
 
         let glight = GroupedLight::new(link_room);
 
@@ -308,6 +678,14 @@ impl Client {
                     log::error!("FAIL: {e:?} in {upd:?}");
                 }
             }
+            Resource::Device(dev) => {
+                if let Err(e) = self.handle_update_sensor(&dev, &upd).await {
+                    log::error!("FAIL: {e:?} in {upd:?}");
+                }
+                if let Err(e) = self.handle_update_button(rid, &upd).await {
+                    log::error!("FAIL: {e:?} in {upd:?}");
+                }
+            }
             _ => {}
         }
 
@@ -315,15 +693,32 @@ impl Client {
     }
 
     async fn handle_update_light(&mut self, uuid: &Uuid, devupd: &DeviceUpdate) -> ApiResult<()> {
+        let color = devupd.color.and_then(|col| col.as_xy());
+
+        /* lights with emulated color temperature report a color, which is
+         * reported back as a temperature if it is (close to) a white point */
+        let emulated_ct = color
+            .filter(|_| self.ct_lights.contains(uuid))
+            .and_then(|xy| {
+                let schema = MirekSchema::DEFAULT;
+                let mirek = xy.to_mirek(schema.mirek_minimum, schema.mirek_maximum);
+                let white = XY::from_mirek(mirek);
+                ((xy.x - white.x).hypot(xy.y - white.y) < 0.01).then_some(mirek)
+            });
+
         let mut res = self.state.lock().await;
         res.update::<Light>(uuid, move |light| {
             let upd = LightUpdate::new()
                 .with_on(devupd.state.map(Into::into))
                 .with_brightness(devupd.brightness.map(|b| b / 254.0 * 100.0))
                 .with_color_temperature(devupd.color_temp)
-                .with_color_xy(devupd.color.and_then(|col| col.xy));
+                .with_color_xy(color);
 
             *light += upd;
+
+            if let (Some(mirek), Some(ct)) = (emulated_ct, &mut light.color_temperature) {
+                ct.mirek = Some(mirek);
+            }
         })?;
 
         for learn in self.learn.values_mut() {
@@ -333,7 +728,7 @@ impl Client {
                 let light = res.get::<Light>(&rlink)?;
                 let mut color_temperature = None;
                 let mut color = None;
-                if let Some(DeviceColor { xy: Some(xy), .. }) = upd.color {
+                if let Some(xy) = upd.color.and_then(|col| col.as_xy()) {
                     color = Some(ColorUpdate { xy });
                 } else if let Some(mirek) = upd.color_temp {
                     color_temperature = Some(ColorTemperatureUpdate { mirek });
@@ -390,15 +785,121 @@ impl Client {
         })
     }
 
+    async fn handle_update_sensor(&self, dev: &Device, upd: &DeviceUpdate) -> ApiResult<()> {
+        let mut res = self.state.lock().await;
+
+        for svc in &dev.services {
+            match svc.rtype {
+                RType::Motion => {
+                    if let Some(occupancy) = upd.occupancy {
+                        res.update::<Motion>(&svc.rid, |motion| motion.report(occupancy))?;
+                    }
+                }
+                RType::LightLevel => {
+                    if let Some(lux) = upd.illuminance_lux.or(upd.illuminance) {
+                        res.update::<LightLevel>(&svc.rid, |light| light.report(lux))?;
+                    }
+                }
+                RType::Temperature => {
+                    if let Some(temp) = upd.temperature {
+                        res.update::<Temperature>(&svc.rid, |temperature| {
+                            temperature.report(temp);
+                        })?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        drop(res);
+
+        Ok(())
+    }
+
+    async fn handle_update_button(&mut self, uuid: &Uuid, upd: &DeviceUpdate) -> ApiResult<()> {
+        let Some(action) = upd.action.as_deref().filter(|act| !act.is_empty()) else {
+            return Ok(());
+        };
+
+        let Some(buttons) = self.buttons.get_mut(uuid) else {
+            return Ok(());
+        };
+
+        let (index, event) = match buttons.actions.get(action) {
+            Some(ButtonAction::Event(index, event)) => {
+                if *event == ButtonEvent::LongPress {
+                    buttons.held = Some(*index);
+                }
+                (*index, *event)
+            }
+            Some(ButtonAction::Stop) => {
+                let Some(index) = buttons.held.take() else {
+                    log::debug!("[{}] Ignoring {action:?} without a held button", self.name);
+                    return Ok(());
+                };
+                (index, ButtonEvent::LongRelease)
+            }
+            None => {
+                log::debug!("[{}] Ignoring unknown action {action:?}", self.name);
+                return Ok(());
+            }
+        };
+
+        let Some(button) = buttons.links.get(index) else {
+            return Ok(());
+        };
+
+        log::debug!("[{}] Button {button} event: {event:?}", self.name);
+
+        let mut res = self.state.lock().await;
+        res.update::<Button>(button, |btn| {
+            btn.button.button_report = Some(ButtonReport {
+                updated: Utc::now(),
+                event,
+            });
+        })
+    }
+
+    /// Report devices joining the network. Joins and interviews are only
+    /// logged: once an interview succeeds, zigbee2mqtt publishes
+    /// `bridge/devices` again, which adds the new device (and sends the add
+    /// events to clients)
+    fn handle_bridge_event(&self, evt: &BridgeEvent) {
+        let Ok(dev) = serde_json::from_value::<BridgeEventDevice>(evt.data.clone()) else {
+            return;
+        };
+
+        match (evt.event_type.as_str(), dev.status.as_deref()) {
+            ("device_joined", _) => log::info!(
+                "[{}] Device {:?} joined: [{}]",
+                self.name,
+                dev.ieee_address,
+                dev.friendly_name
+            ),
+            ("device_interview", Some("successful")) => log::info!(
+                "[{}] Device {:?} interviewed: [{}]",
+                self.name,
+                dev.ieee_address,
+                dev.friendly_name
+            ),
+            ("device_interview", Some("failed")) => log::warn!(
+                "[{}] Interview of device {:?} failed: [{}]",
+                self.name,
+                dev.ieee_address,
+                dev.friendly_name
+            ),
+            _ => {}
+        }
+    }
+
     async fn handle_bridge_message(&mut self, msg: Message) -> ApiResult<()> {
         #[allow(unused_variables)]
         match msg {
-            Message::BridgeInfo(ref obj) => { /* println!("{obj:#?}"); */ }
+            Message::BridgeInfo(ref obj) => self.handle_bridge_info(obj).await?,
             Message::BridgeLogging(ref obj) => { /* println!("{obj:#?}"); */ }
             Message::BridgeExtensions(ref obj) => { /* println!("{obj:#?}"); */ }
-            Message::BridgeEvent(ref obj) => { /* println!("{obj:#?}"); */ }
+            Message::BridgeEvent(ref obj) => self.handle_bridge_event(obj),
             Message::BridgeDefinitions(ref obj) => { /* println!("{obj:#?}"); */ }
-            Message::BridgeState(ref obj) => { /* println!("{obj:#?}"); */ }
+            Message::BridgeState(ref obj) => self.handle_bridge_state(&obj.state).await?,
 
             Message::BridgeDevices(ref obj) => {
                 for dev in obj {
@@ -411,26 +912,32 @@ impl Client {
                             dev.model_id.as_deref().unwrap_or("<unknown model>")
                         );
                         self.add_light(dev, exp).await?;
-                    } else {
-                        log::debug!(
-                            "[{}] Ignoring unsupported device {}",
+                    } else if let Some(switch) = dev.expose_plug() {
+                        log::info!(
+                            "[{}] Adding plug {:?}: [{}] ({})",
                             self.name,
-                            dev.friendly_name
+                            dev.ieee_address,
+                            dev.friendly_name,
+                            dev.model_id.as_deref().unwrap_or("<unknown model>")
                         );
-                        self.ignore.insert(dev.friendly_name.to_string());
-                    }
-                    /*
-                    if dev.expose_action() {
+                        self.add_light(dev, &ExposeLight::from(switch)).await?;
+                    } else if dev.expose_sensor() || !dev.expose_action_values().is_empty() {
                         log::info!(
-                            "[{}] Adding switch {:?}: [{}] ({})",
+                            "[{}] Adding device {:?}: [{}] ({})",
                             self.name,
                             dev.ieee_address,
                             dev.friendly_name,
                             dev.model_id.as_deref().unwrap_or("<unknown model>")
                         );
-                        self.add_switch(dev).await?;
+                        self.add_device(dev).await?;
+                    } else {
+                        log::debug!(
+                            "[{}] Ignoring unsupported device {}",
+                            self.name,
+                            dev.friendly_name
+                        );
+                        self.ignore.insert(dev.friendly_name.to_string());
                     }
-                    */
                 }
             }
 
@@ -444,11 +951,125 @@ impl Client {
         Ok(())
     }
 
+    /// Set the zigbee connectivity status of the device behind a z2m topic
+    fn set_connectivity(
+        res: &mut Resources,
+        uuid: &Uuid,
+        status: ZigbeeConnectivityStatus,
+    ) -> ApiResult<()> {
+        /* lights are mapped by their light service, other devices directly */
+        let device = res
+            .get::<Light>(&RType::Light.link_to(*uuid))
+            .map_or_else(|_| RType::Device.link_to(*uuid), |light| light.owner);
+
+        let Ok(dev) = res.get::<Device>(&device) else {
+            return Ok(());
+        };
+
+        let Some(zbc) = dev
+            .services
+            .iter()
+            .find(|svc| svc.rtype == RType::ZigbeeConnectivity)
+            .copied()
+        else {
+            return Ok(());
+        };
+
+        if res.get::<ZigbeeConnectivity>(&zbc)?.status != status {
+            res.update::<ZigbeeConnectivity>(&zbc.rid, |zbc| zbc.status = status)?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_availability(&self, topic: &str, payload: &Value) -> ApiResult<()> {
+        let Some(uuid) = self.map.get(topic) else {
+            return Ok(());
+        };
+
+        let msg: AvailabilityMessage = serde_json::from_value(payload.clone())?;
+        let status = match msg.availability() {
+            Availability::Online => ZigbeeConnectivityStatus::Connected,
+            Availability::Offline => ZigbeeConnectivityStatus::Disconnected,
+        };
+
+        log::debug!("[{}] Device {topic} is {status:?}", self.name);
+
+        Self::set_connectivity(&mut *self.state.lock().await, uuid, status)
+    }
+
+    /// Report the coordinator and network of this server as the zigbee
+    /// connectivity of the bridge
+    async fn handle_bridge_info(&self, info: &BridgeInfo) -> ApiResult<()> {
+        let mac_address = info.coordinator.ieee_address.mac_address();
+        let channel = json!({
+            "status": "set",
+            "value": format!("channel_{}", info.network.channel),
+        });
+
+        /* older versions of zigbee2mqtt report the pan id as a number */
+        let extended_pan_id = match &info.network.extended_pan_id {
+            Value::Number(num) => num.as_u64().map(|num| format!("{num:016x}")),
+            Value::String(text) => Some(text.trim_start_matches("0x").to_string()),
+            _ => None,
+        };
+
+        log::debug!(
+            "[{}] Coordinator {mac_address} on channel {}",
+            self.name,
+            info.network.channel
+        );
+
+        self.state
+            .lock()
+            .await
+            .update_bridge_connectivity(&self.name, |zbc| {
+                zbc.mac_address = mac_address;
+                zbc.channel = Some(channel);
+                zbc.extended_pan_id = extended_pan_id;
+                zbc.status = ZigbeeConnectivityStatus::Connected;
+            })
+    }
+
+    /// Set the connectivity status of all devices on this server, e.g.
+    /// when zigbee2mqtt itself goes offline
+    async fn handle_bridge_state(&self, state: &BridgeOnlineState) -> ApiResult<()> {
+        /* when zigbee2mqtt comes back, it reports the availability of each
+         * device again (if enabled), so assume the best until then */
+        let status = match state {
+            BridgeOnlineState::Online => ZigbeeConnectivityStatus::Connected,
+            BridgeOnlineState::Offline => ZigbeeConnectivityStatus::Disconnected,
+        };
+
+        log::info!("[{}] Zigbee2mqtt is {state:?}", self.name);
+
+        let mut res = self.state.lock().await;
+        for uuid in self.map.values() {
+            Self::set_connectivity(&mut res, uuid, status)?;
+        }
+        res.update_bridge_connectivity(&self.name, |zbc| zbc.status = status)?;
+        drop(res);
+
+        Ok(())
+    }
+
     async fn handle_device_message(&mut self, msg: RawMessage) -> ApiResult<()> {
+        if let Some(topic) = msg.topic.strip_suffix("/availability") {
+            if let Err(err) = self.handle_availability(topic, &msg.payload).await {
+                log::error!(
+                    "[{}] Cannot parse availability of {topic}: {err}",
+                    self.name
+                );
+            }
+            return Ok(());
+        }
+
         if msg.topic.contains('/') {
             return Ok(());
         }
 
+        self.handle_link_button(&msg).await;
+
         let Some(ref val) = self.map.get(&msg.topic).copied() else {
             if !self.ignore.contains(&msg.topic) {
                 log::warn!(
@@ -472,30 +1093,40 @@ impl Client {
         Ok(())
     }
 
-    async fn websocket_read(&mut self, pkt: tungstenite::Message) -> ApiResult<()> {
-        let tungstenite::Message::Text(txt) = pkt else {
-            log::error!("[{}] Received non-text message on websocket :(", self.name);
-            return Err(ApiError::UnexpectedZ2mReply(pkt));
+    async fn handle_link_button(&self, msg: &RawMessage) {
+        let conf = &self.config.bridge.linkbutton;
+
+        if conf.z2m_device.as_ref() != Some(&msg.topic) {
+            return;
+        }
+
+        let Some(action) = msg.payload.get("action").and_then(Value::as_str) else {
+            return;
         };
 
-        let raw_msg: Result<RawMessage, _> = serde_json::from_str(&txt);
+        if action.is_empty() || conf.z2m_action.as_ref().is_some_and(|act| act != action) {
+            return;
+        }
 
-        let msg = raw_msg.map_err(|err| {
-            log::error!(
-                "[{}] Invalid websocket message: {:#?} [{}..]",
-                self.name,
-                err,
-                &txt.chars().take(128).collect::<String>()
-            );
-            err
-        })?;
+        log::info!(
+            "[{}] Link button pressed by {} ({action})",
+            self.name,
+            msg.topic
+        );
+
+        self.state
+            .lock()
+            .await
+            .press_link_button(conf.press_duration(None));
+    }
 
+    async fn handle_message(&mut self, msg: RawMessage) -> ApiResult<()> {
         /* bridge messages are handled differently. everything else is a device message */
         if !msg.topic.starts_with("bridge/") {
             return self.handle_device_message(msg).await;
         }
 
-        match serde_json::from_str(&txt) {
+        match serde_json::to_value(&msg).and_then(serde_json::from_value) {
             Ok(bridge_msg) => self.handle_bridge_message(bridge_msg).await,
             Err(err) => {
                 match msg.topic.as_str() {
@@ -527,7 +1158,7 @@ impl Client {
     fn learn_cleanup(&mut self) {
         let now = Utc::now();
         self.learn.retain(|uuid, lscene| {
-            let res = lscene.expire < now;
+            let res = lscene.expire >= now;
             if !res {
                 log::warn!(
                     "[{}] Failed to learn scene {uuid} before deadline",
@@ -544,15 +1175,18 @@ impl Client {
         let scene: &Scene = lock.get(lscene)?;
 
         if scene.actions.is_empty() {
-            let room: &Room = lock.get(&scene.group)?;
-
-            let lights: Vec<Uuid> = room
-                .children
-                .iter()
-                .filter_map(|rl| lock.get(rl).ok())
-                .filter_map(Device::light_service)
-                .map(|rl| rl.rid)
-                .collect();
+            let lights: Vec<Uuid> = if scene.group.rtype == RType::Zone {
+                let zone: &Zone = lock.get(&scene.group)?;
+                zone.children.iter().map(|rl| rl.rid).collect()
+            } else {
+                let room: &Room = lock.get(&scene.group)?;
+                room.children
+                    .iter()
+                    .filter_map(|rl| lock.get(rl).ok())
+                    .filter_map(Device::light_service)
+                    .map(|rl| rl.rid)
+                    .collect()
+            };
 
             drop(lock);
 
@@ -568,11 +1202,11 @@ impl Client {
         Ok(())
     }
 
-    async fn websocket_send<'a>(
+    async fn transport_send(
         &self,
-        socket: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+        socket: &mut Transport,
         topic: &str,
-        payload: Z2mRequest<'a>,
+        payload: Z2mRequest<'_>,
     ) -> ApiResult<()> {
         let Some(uuid) = self.map.get(topic) else {
             log::trace!(
@@ -590,28 +1224,105 @@ impl Client {
             payload: serde_json::to_value(payload)?,
             topic: format!("{topic}/set"),
         };
-        let json = serde_json::to_string(&api_req)?;
-        log::debug!("[{}] Sending {json}", self.name);
-        let msg = tungstenite::Message::Text(json);
-        Ok(socket.send(msg).await?)
+        log::debug!(
+            "[{}] Sending {}",
+            self.name,
+            serde_json::to_string(&api_req)?
+        );
+        socket.send(&api_req).await
+    }
+
+    /// Send a request to the zigbee2mqtt bridge itself (`bridge/request/{path}`)
+    async fn bridge_request(
+        &self,
+        socket: &mut Transport,
+        path: &str,
+        payload: Value,
+    ) -> ApiResult<()> {
+        let api_req = RawMessage {
+            topic: format!("bridge/request/{path}"),
+            payload,
+        };
+        log::debug!(
+            "[{}] Sending {}",
+            self.name,
+            serde_json::to_string(&api_req)?
+        );
+        socket.send(&api_req).await
+    }
+
+    /// Topic of a device (or any of its services) known on this z2m connection
+    fn device_topic(&self, res: &Resources, device: &ResourceLink) -> Option<String> {
+        if let Some(topic) = self.rmap.get(&device.rid) {
+            return Some(topic.clone());
+        }
+        let dev = res.get::<Device>(device).ok()?;
+        dev.services
+            .iter()
+            .find_map(|svc| self.rmap.get(&svc.rid))
+            .cloned()
     }
 
-    async fn websocket_write(
+    /// Adapt an update to the capabilities of a light: emulated color
+    /// temperature, color gamut, and lights without xy support
+    fn adapt_light_update(
+        &self,
+        res: &Resources,
+        light: &ResourceLink,
+        upd: &DeviceUpdate,
+    ) -> DeviceUpdate {
+        let mut upd = upd.clone();
+
+        /* only send effects the light supports, z2m reports an error otherwise */
+        if upd.effect.is_some() {
+            let effects = self.effects.get(&light.rid).map_or(&[][..], Vec::as_slice);
+            let identify = self.identify_lights.contains(&light.rid);
+            upd = upd.with_supported_effect(effects, identify);
+        }
+
+        if self.ct_lights.contains(&light.rid) {
+            if let Some(converted) = upd.converted_from_ct() {
+                upd = converted;
+            }
+        }
+
+        /* colors outside the gamut of the light are shown as the
+         * closest color it can produce */
+        let gamut = res
+            .get::<Light>(light)
+            .ok()
+            .and_then(|light| light.color.as_ref()?.gamut.as_ref());
+        if let Some(clamped) = gamut.and_then(|gamut| upd.clamped_to(gamut)) {
+            upd = clamped;
+        }
+
+        if self.hs_lights.contains(&light.rid) {
+            if let Some(converted) = upd.converted_to_hs() {
+                upd = converted;
+            }
+        }
+
+        upd
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn transport_write(
         &mut self,
-        socket: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+        socket: &mut Transport,
         req: Arc<ClientRequest>,
     ) -> ApiResult<()> {
         self.learn_cleanup();
 
-        let lock = self.state.lock().await;
+        let mut lock = self.state.lock().await;
 
         match &*req {
             ClientRequest::LightUpdate { device, upd } => {
+                let upd = self.adapt_light_update(&lock, device, upd);
                 drop(lock);
                 if let Some(topic) = self.rmap.get(&device.rid) {
-                    let z2mreq = Z2mRequest::Update(upd);
-                    self.websocket_send(socket, topic, z2mreq).await?;
-                };
+                    let z2mreq = Z2mRequest::Update(&upd);
+                    self.transport_send(socket, topic, z2mreq).await?;
+                }
             }
 
             ClientRequest::GroupUpdate { device, upd } => {
@@ -620,7 +1331,7 @@ impl Client {
 
                 if let Some(topic) = self.rmap.get(&room) {
                     let z2mreq = Z2mRequest::Update(upd);
-                    self.websocket_send(socket, topic, z2mreq).await?;
+                    self.transport_send(socket, topic, z2mreq).await?;
                 }
             }
 
@@ -628,11 +1339,11 @@ impl Client {
                 drop(lock);
                 if let Some(topic) = self.rmap.get(&room.rid) {
                     let z2mreq = Z2mRequest::SceneStore { name, id: *id };
-                    self.websocket_send(socket, topic, z2mreq).await?;
+                    self.transport_send(socket, topic, z2mreq).await?;
                 }
             }
 
-            ClientRequest::SceneRecall { scene } => {
+            ClientRequest::SceneRecall { scene, transition } => {
                 let room = lock.get::<Scene>(scene)?.group.rid;
                 let index = lock
                     .aux_get(scene)?
@@ -641,8 +1352,50 @@ impl Client {
                 drop(lock);
                 if let Some(topic) = self.rmap.get(&room).cloned() {
                     self.learn_scene_recall(scene).await?;
-                    let z2mreq = Z2mRequest::SceneRecall(index);
-                    self.websocket_send(socket, &topic, z2mreq).await?;
+                    let z2mreq = Z2mRequest::SceneRecall {
+                        scene_recall: index,
+                        transition: *transition,
+                    };
+                    self.transport_send(socket, &topic, z2mreq).await?;
+                }
+            }
+
+            ClientRequest::SceneAdd { scene } => {
+                let obj = lock.get::<Scene>(scene)?;
+                let id = lock
+                    .aux_get(scene)?
+                    .index
+                    .ok_or(ApiError::NotFound(scene.rid))?;
+
+                /* scenes are stored in the lights themselves, for the z2m group */
+                let Some(group_id) = lock.aux_get(&obj.group).ok().and_then(|aux| aux.index) else {
+                    return Ok(());
+                };
+                let name = obj.metadata.name.clone();
+                let states: Vec<_> = obj
+                    .actions
+                    .iter()
+                    .map(|act| {
+                        let state = DeviceUpdate::from(&act.action);
+                        (
+                            act.target,
+                            self.adapt_light_update(&lock, &act.target, &state),
+                        )
+                    })
+                    .collect();
+                drop(lock);
+
+                for (target, state) in &states {
+                    let Some(topic) = self.rmap.get(&target.rid).cloned() else {
+                        continue;
+                    };
+                    let z2mreq = Z2mRequest::SceneAdd {
+                        id,
+                        group_id,
+                        name: &name,
+                        state,
+                    };
+                    self.transport_send(socket, &topic, z2mreq).await?;
                 }
             }
 
@@ -656,9 +1409,171 @@ impl Client {
 
                 if let Some(topic) = self.rmap.get(&room).cloned() {
                     let z2mreq = Z2mRequest::SceneRemove(index);
-                    self.websocket_send(socket, &topic, z2mreq).await?;
+                    self.transport_send(socket, &topic, z2mreq).await?;
                 }
             }
+
+            ClientRequest::GroupAdd { room } => {
+                let obj = lock.get::<Room>(room)?;
+                let members: Vec<String> = obj
+                    .children
+                    .iter()
+                    .filter_map(|dev| self.device_topic(&lock, dev))
+                    .collect();
+
+                /* only the z2m server that knows the room members creates the group */
+                if members.is_empty() {
+                    log::debug!(
+                        "[{}] No members of room {} known here, ignoring",
+                        self.name,
+                        room.rid
+                    );
+                    return Ok(());
+                }
+
+                let prefix = self.server.group_prefix.as_deref().unwrap_or_default();
+                let topic = format!("{prefix}{}", obj.metadata.name);
+                drop(lock);
+
+                log::info!("[{}] Creating group {topic:?}", self.name);
+
+                self.state
+                    .lock()
+                    .await
+                    .aux_set(room, AuxData::new().with_topic(&topic));
+                self.rmap.insert(room.rid, topic.clone());
+
+                self.bridge_request(socket, "group/add", json!({"friendly_name": topic}))
+                    .await?;
+                for device in members {
+                    let req = json!({"group": topic, "device": device});
+                    self.bridge_request(socket, "group/members/add", req)
+                        .await?;
+                }
+            }
+
+            ClientRequest::GroupRemove { room } => {
+                drop(lock);
+                if let Some(topic) = self.rmap.remove(&room.rid) {
+                    log::info!("[{}] Removing group {topic:?}", self.name);
+                    self.map.remove(&topic);
+                    self.rmap.retain(|_, name| *name != topic);
+                    self.bridge_request(socket, "group/remove", json!({"id": topic}))
+                        .await?;
+                }
+            }
+
+            ClientRequest::GroupRename { room, name } => {
+                let Some(topic) = self.rmap.get(&room.rid).cloned() else {
+                    return Ok(());
+                };
+                let prefix = self.server.group_prefix.as_deref().unwrap_or_default();
+                let new_topic = format!("{prefix}{name}");
+
+                log::info!("[{}] Renaming group {topic:?} to {new_topic:?}", self.name);
+
+                let mut lock = lock;
+                let aux = lock.aux_get(room).cloned().unwrap_or_default();
+                lock.aux_set(room, aux.with_topic(&new_topic));
+                drop(lock);
+
+                if let Some(uuid) = self.map.remove(&topic) {
+                    self.map.insert(new_topic.clone(), uuid);
+                }
+                for name in self.rmap.values_mut().filter(|name| **name == topic) {
+                    name.clone_from(&new_topic);
+                }
+
+                let req = json!({"from": topic, "to": new_topic});
+                self.bridge_request(socket, "group/rename", req).await?;
+            }
+
+            ClientRequest::GroupMemberAdd { room, device } => {
+                let member = self.device_topic(&lock, device);
+                drop(lock);
+                if let (Some(topic), Some(member)) = (self.rmap.get(&room.rid), member) {
+                    let req = json!({"group": topic, "device": member});
+                    self.bridge_request(socket, "group/members/add", req)
+                        .await?;
+                }
+            }
+
+            ClientRequest::GroupMemberRemove { room, device } => {
+                let member = self.device_topic(&lock, device);
+                drop(lock);
+                if let (Some(topic), Some(member)) = (self.rmap.get(&room.rid), member) {
+                    let req = json!({"group": topic, "device": member});
+                    self.bridge_request(socket, "group/members/remove", req)
+                        .await?;
+                }
+            }
+
+            ClientRequest::DeviceRename { device, name } => {
+                let Some(topic) = self.device_topic(&lock, device) else {
+                    return Ok(());
+                };
+
+                log::info!("[{}] Renaming device {topic:?} to {name:?}", self.name);
+
+                /* all services of the device share its topic */
+                let services = lock
+                    .get::<Device>(device)
+                    .map(|dev| dev.services.clone())
+                    .unwrap_or_default();
+                for link in services.iter().chain([device]) {
+                    if let Ok(aux) = lock.aux_get(link).cloned() {
+                        if aux.topic.as_ref() == Some(&topic) {
+                            lock.aux_set(link, aux.with_topic(name));
+                        }
+                    }
+                }
+                drop(lock);
+
+                if let Some(uuid) = self.map.remove(&topic) {
+                    self.map.insert(name.clone(), uuid);
+                }
+                for value in self.rmap.values_mut().filter(|value| **value == topic) {
+                    value.clone_from(name);
+                }
+
+                let req = json!({"from": topic, "to": name});
+                self.bridge_request(socket, "device/rename", req).await?;
+            }
+
+            ClientRequest::DeviceRemove { device, services } => {
+                drop(lock);
+                let Some(topic) = std::iter::once(device)
+                    .chain(services)
+                    .find_map(|link| self.rmap.get(&link.rid))
+                    .cloned()
+                else {
+                    return Ok(());
+                };
+
+                /* forget the device, so further messages from it are ignored */
+                self.map.remove(&topic);
+                self.rmap.retain(|_, name| *name != topic);
+
+                if self.server.remove_devices {
+                    log::info!("[{}] Removing device {topic:?}", self.name);
+                    self.bridge_request(socket, "device/remove", json!({"id": topic}))
+                        .await?;
+                } else {
+                    log::info!(
+                        "[{}] Device {topic:?} deleted, but not removed from zigbee2mqtt (remove_devices is off)",
+                        self.name
+                    );
+                }
+            }
+
+            ClientRequest::PermitJoin { seconds } => {
+                drop(lock);
+                log::info!("[{}] Permitting devices to join for {seconds}s", self.name);
+
+                /* "value" is only used by zigbee2mqtt before 2.0 */
+                let req = json!({"value": true, "time": seconds});
+                self.bridge_request(socket, "permit_join", req).await?;
+            }
         }
 
         Ok(())
@@ -667,17 +1582,26 @@ impl Client {
     pub async fn event_loop(
         &mut self,
         chan: &mut Receiver<Arc<ClientRequest>>,
-        mut socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        mut socket: Transport,
     ) -> ApiResult<()> {
         loop {
             select! {
                 pkt = chan.recv() => {
-                    let api_req = pkt?;
-                    self.websocket_write(&mut socket, api_req).await?;
+                    let api_req = match pkt {
+                        Ok(api_req) => api_req,
+                        /* a burst of requests (e.g. entertainment streaming) can
+                         * overrun the channel. drop the oldest, but keep going */
+                        Err(RecvError::Lagged(count)) => {
+                            log::warn!("[{}] Dropped {count} pending requests", self.name);
+                            continue;
+                        }
+                        Err(err) => return Err(err.into()),
+                    };
+                    self.transport_write(&mut socket, api_req).await?;
                     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                 },
-                pkt = socket.next() => {
-                    self.websocket_read(pkt.ok_or(ApiError::UnexpectedZ2mEof)??).await?;
+                pkt = socket.recv() => {
+                    self.handle_message(pkt?).await?;
                 },
             };
         }
@@ -687,8 +1611,8 @@ impl Client {
         let mut chan = self.state.lock().await.z2m_channel();
         loop {
             log::info!("[{}] Connecting to {}", self.name, self.server.url);
-            match connect_async(&self.server.url).await {
-                Ok((socket, _)) => {
+            match Transport::connect(&self.server).await {
+                Ok(socket) => {
                     let res = self.event_loop(&mut chan, socket).await;
                     if let Err(err) = res {
                         log::error!("[{}] Event loop broke: {err}", self.name);
@@ -698,9 +1622,112 @@ impl Client {
                     log::error!("[{}] Connect failed: {err:?}", self.name);
                 }
             }
-            sleep(std::time::Duration::from_millis(2000)).await;
+            sleep(std::time::Duration::from_secs(2)).await;
+        }
+    }
+}
+
+/// Buttons (name and event values, in order of appearance) found in the
+/// action values of a device, and what each action value does to them.
+///
+/// A `<name>_stop` action (ikea) ends the `<name>_move_<direction>` action
+/// of whichever button is held, so it is not a button of its own.
+fn parse_actions(values: &[String]) -> (Vec<(String, Vec<ButtonEvent>)>, ButtonActions) {
+    let mut buttons: Vec<(String, Vec<ButtonEvent>)> = vec![];
+    let mut actions = HashMap::new();
+
+    let has_stop = |prefix: &str| values.iter().any(|val| *val == format!("{prefix}_stop"));
+    let has_move = |prefix: &str| {
+        let moving = format!("{prefix}_move_");
+        values.iter().any(|val| val.starts_with(&moving))
+    };
+
+    for action in values {
+        if action.strip_suffix("_stop").is_some_and(has_move) {
+            actions.insert(action.clone(), ButtonAction::Stop);
+            continue;
+        }
+
+        let (name, event) = parse_action(action);
+
+        let index = buttons
+            .iter()
+            .position(|(n, _)| *n == name)
+            .unwrap_or_else(|| {
+                buttons.push((name, vec![]));
+                buttons.len() - 1
+            });
+
+        let evs = &mut buttons[index].1;
+        if !evs.contains(&event) {
+            evs.push(event);
+        }
+
+        /* moving buttons report a long release when stopped */
+        if let Some((prefix, _)) = action.split_once("_move_") {
+            if has_stop(prefix) && !evs.contains(&ButtonEvent::LongRelease) {
+                evs.push(ButtonEvent::LongRelease);
+            }
+        }
+
+        actions.insert(action.clone(), ButtonAction::Event(index, event));
+    }
+
+    (buttons, actions)
+}
+
+/// Split a z2m action value into the name of the physical button, and the
+/// hue button event it represents.
+///
+/// Covers the common naming schemes, e.g. `on_press_release` (hue dimmer),
+/// `arrow_left_hold` or `brightness_move_up` (ikea), `button_1_single`,
+/// `double_left` or just `double` (aqara).
+fn parse_action(action: &str) -> (String, ButtonEvent) {
+    const EVENTS: &[(&str, ButtonEvent)] = &[
+        ("press_release", ButtonEvent::ShortRelease),
+        ("hold_release", ButtonEvent::LongRelease),
+        ("long_release", ButtonEvent::LongRelease),
+        ("release", ButtonEvent::LongRelease),
+        ("stop", ButtonEvent::LongRelease),
+        ("press", ButtonEvent::InitialPress),
+        ("hold", ButtonEvent::Repeat),
+        ("long", ButtonEvent::Repeat),
+        ("double", ButtonEvent::DoubleShortRelease),
+        ("single", ButtonEvent::ShortRelease),
+        ("click", ButtonEvent::ShortRelease),
+    ];
+
+    /* "brightness_move_up" is a long press of the "brightness_up" button */
+    if let Some((name, direction)) = action.split_once("_move_") {
+        return (format!("{name}_{direction}"), ButtonEvent::LongPress);
+    }
+
+    /* "<button>_<event>" */
+    for (suffix, event) in EVENTS {
+        if action == *suffix {
+            return (String::new(), *event);
+        }
+
+        if let Some(name) = action
+            .strip_suffix(suffix)
+            .and_then(|name| name.strip_suffix('_'))
+        {
+            return (name.to_string(), *event);
         }
     }
+
+    /* "<event>_<button>" */
+    for (prefix, event) in EVENTS {
+        if let Some(name) = action
+            .strip_prefix(prefix)
+            .and_then(|name| name.strip_prefix('_'))
+        {
+            return (name.to_string(), *event);
+        }
+    }
+
+    /* plain actions (e.g. "on", "off", "toggle") are buttons of their own */
+    (action.to_string(), ButtonEvent::ShortRelease)
 }
 
 #[allow(clippy::match_same_arms)]
@@ -730,3 +1757,83 @@ fn guess_scene_icon(name: &str) -> Option<ResourceLink> {
         rtype: RType::PublicImage,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(actions: &[&str]) -> Vec<String> {
+        actions.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn aqara_event_before_button() {
+        let (buttons, actions) = parse_actions(&values(&[
+            "single_left",
+            "double_left",
+            "hold_left",
+            "single_right",
+            "hold_right",
+        ]));
+
+        let names: Vec<&str> = buttons.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["left", "right"]);
+        assert_eq!(
+            buttons[0].1,
+            [
+                ButtonEvent::ShortRelease,
+                ButtonEvent::DoubleShortRelease,
+                ButtonEvent::Repeat
+            ]
+        );
+        assert_eq!(
+            actions["hold_right"],
+            ButtonAction::Event(1, ButtonEvent::Repeat)
+        );
+    }
+
+    #[test]
+    fn ikea_move_and_stop() {
+        let (buttons, actions) = parse_actions(&values(&[
+            "on",
+            "off",
+            "brightness_move_up",
+            "brightness_move_down",
+            "brightness_stop",
+        ]));
+
+        let names: Vec<&str> = buttons.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["on", "off", "brightness_up", "brightness_down"]);
+        for (_, events) in &buttons[2..] {
+            assert_eq!(events, &[ButtonEvent::LongPress, ButtonEvent::LongRelease]);
+        }
+        assert_eq!(
+            actions["brightness_move_down"],
+            ButtonAction::Event(3, ButtonEvent::LongPress)
+        );
+        assert_eq!(actions["brightness_stop"], ButtonAction::Stop);
+    }
+
+    #[test]
+    fn plain_stop_is_a_button() {
+        let (buttons, actions) = parse_actions(&values(&["open", "close", "stop"]));
+
+        assert_eq!(buttons.len(), 3);
+        assert_eq!(
+            actions["stop"],
+            ButtonAction::Event(2, ButtonEvent::LongRelease)
+        );
+    }
+
+    #[test]
+    fn event_after_button() {
+        assert_eq!(
+            parse_action("on_press_release"),
+            ("on".to_string(), ButtonEvent::ShortRelease)
+        );
+        assert_eq!(
+            parse_action("arrow_left_hold"),
+            ("arrow_left".to_string(), ButtonEvent::Repeat)
+        );
+    }
+}
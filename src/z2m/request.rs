@@ -23,11 +23,53 @@ pub enum ClientRequest {
 
     SceneRecall {
         scene: ResourceLink,
+        transition: Option<f64>,
+    },
+
+    SceneAdd {
+        scene: ResourceLink,
     },
 
     SceneRemove {
         scene: ResourceLink,
     },
+
+    GroupAdd {
+        room: ResourceLink,
+    },
+
+    GroupRemove {
+        room: ResourceLink,
+    },
+
+    GroupRename {
+        room: ResourceLink,
+        name: String,
+    },
+
+    GroupMemberAdd {
+        room: ResourceLink,
+        device: ResourceLink,
+    },
+
+    GroupMemberRemove {
+        room: ResourceLink,
+        device: ResourceLink,
+    },
+
+    DeviceRename {
+        device: ResourceLink,
+        name: String,
+    },
+
+    DeviceRemove {
+        device: ResourceLink,
+        services: Vec<ResourceLink>,
+    },
+
+    PermitJoin {
+        seconds: u64,
+    },
 }
 
 impl ClientRequest {
@@ -47,14 +89,59 @@ impl ClientRequest {
     }
 
     #[must_use]
-    pub const fn scene_recall(scene: ResourceLink) -> Self {
-        Self::SceneRecall { scene }
+    pub const fn scene_recall(scene: ResourceLink, transition: Option<f64>) -> Self {
+        Self::SceneRecall { scene, transition }
+    }
+
+    #[must_use]
+    pub const fn scene_add(scene: ResourceLink) -> Self {
+        Self::SceneAdd { scene }
     }
 
     #[must_use]
     pub const fn scene_store(room: ResourceLink, id: u32, name: String) -> Self {
         Self::SceneStore { room, id, name }
     }
+
+    #[must_use]
+    pub const fn group_add(room: ResourceLink) -> Self {
+        Self::GroupAdd { room }
+    }
+
+    #[must_use]
+    pub const fn group_remove(room: ResourceLink) -> Self {
+        Self::GroupRemove { room }
+    }
+
+    #[must_use]
+    pub const fn group_rename(room: ResourceLink, name: String) -> Self {
+        Self::GroupRename { room, name }
+    }
+
+    #[must_use]
+    pub const fn group_member_add(room: ResourceLink, device: ResourceLink) -> Self {
+        Self::GroupMemberAdd { room, device }
+    }
+
+    #[must_use]
+    pub const fn group_member_remove(room: ResourceLink, device: ResourceLink) -> Self {
+        Self::GroupMemberRemove { room, device }
+    }
+
+    #[must_use]
+    pub const fn device_rename(device: ResourceLink, name: String) -> Self {
+        Self::DeviceRename { device, name }
+    }
+
+    #[must_use]
+    pub const fn device_remove(device: ResourceLink, services: Vec<ResourceLink>) -> Self {
+        Self::DeviceRemove { device, services }
+    }
+
+    #[must_use]
+    pub const fn permit_join(seconds: u64) -> Self {
+        Self::PermitJoin { seconds }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -66,10 +153,24 @@ pub enum Z2mRequest<'a> {
         id: u32,
     },
 
-    SceneRecall(u32),
+    SceneAdd {
+        #[serde(rename = "ID")]
+        id: u32,
+        group_id: u32,
+        name: &'a str,
+        #[serde(flatten)]
+        state: &'a DeviceUpdate,
+    },
 
     SceneRemove(u32),
 
+    #[serde(untagged)]
+    SceneRecall {
+        scene_recall: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        transition: Option<f64>,
+    },
+
     #[serde(untagged)]
     Update(&'a DeviceUpdate),
 }
@@ -0,0 +1,201 @@
+use std::fs;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use rumqttc::{AsyncClient, Event, EventLoop, Incoming, MqttOptions, QoS};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+use crate::config::Z2mServer;
+use crate::error::{ApiError, ApiResult};
+use crate::z2m::api::RawMessage;
+
+/// Connection to a zigbee2mqtt server, either through the websocket frontend,
+/// or directly through the mqtt broker.
+///
+/// Both transports exchange [`RawMessage`]s, with topics relative to the
+/// zigbee2mqtt base topic (e.g. "bridge/devices" or "kitchen/set").
+pub enum Transport {
+    Websocket(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+    Mqtt(MqttTransport),
+}
+
+impl Transport {
+    pub async fn connect(server: &Z2mServer) -> ApiResult<Self> {
+        if server.is_mqtt() {
+            Ok(Self::Mqtt(MqttTransport::connect(server).await?))
+        } else {
+            let (socket, _) = connect_async(&server.url).await?;
+            Ok(Self::Websocket(Box::new(socket)))
+        }
+    }
+
+    pub async fn recv(&mut self) -> ApiResult<RawMessage> {
+        match self {
+            Self::Websocket(socket) => {
+                let pkt = socket.next().await.ok_or(ApiError::UnexpectedZ2mEof)??;
+
+                let tungstenite::Message::Text(txt) = pkt else {
+                    log::error!("Received non-text message on websocket :(");
+                    return Err(ApiError::UnexpectedZ2mReply(pkt));
+                };
+
+                serde_json::from_str(&txt).map_err(|err| {
+                    log::error!(
+                        "Invalid websocket message: {:#?} [{}..]",
+                        err,
+                        &txt.chars().take(128).collect::<String>()
+                    );
+                    err.into()
+                })
+            }
+            Self::Mqtt(mqtt) => mqtt.recv().await,
+        }
+    }
+
+    pub async fn send(&mut self, msg: &RawMessage) -> ApiResult<()> {
+        match self {
+            Self::Websocket(socket) => {
+                let json = serde_json::to_string(msg)?;
+                Ok(socket.send(tungstenite::Message::Text(json)).await?)
+            }
+            Self::Mqtt(mqtt) => mqtt.send(msg).await,
+        }
+    }
+}
+
+pub struct MqttTransport {
+    client: AsyncClient,
+    base_topic: String,
+    rx: mpsc::Receiver<RawMessage>,
+    task: JoinHandle<()>,
+}
+
+impl MqttTransport {
+    /* bridge/devices can easily be several hundred kilobytes */
+    const MAX_PACKET_SIZE: usize = 16 * 1024 * 1024;
+
+    const DEFAULT_PORT: u16 = 1883;
+    const DEFAULT_TLS_PORT: u16 = 8883;
+
+    fn options(server: &Z2mServer) -> ApiResult<MqttOptions> {
+        let (tls, rest) = if let Some(rest) = server.url.strip_prefix("mqtts://") {
+            (true, rest)
+        } else if let Some(rest) = server.url.strip_prefix("mqtt://") {
+            (false, rest)
+        } else {
+            return Err(ApiError::InvalidZ2mUrl(server.url.clone()));
+        };
+
+        let hostport = rest.trim_end_matches('/');
+        let (host, port) = match hostport.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse()?),
+            None if tls => (hostport, Self::DEFAULT_TLS_PORT),
+            None => (hostport, Self::DEFAULT_PORT),
+        };
+
+        let client_id = format!("bifrost-{}", &Uuid::new_v4().simple().to_string()[..12]);
+
+        let mut opts = MqttOptions::new(client_id, host, port);
+        opts.set_keep_alive(Duration::from_secs(30));
+        opts.set_max_packet_size(Self::MAX_PACKET_SIZE, Self::MAX_PACKET_SIZE);
+
+        if let Some(username) = &server.username {
+            opts.set_credentials(username, server.password.as_deref().unwrap_or_default());
+        }
+
+        if tls {
+            let transport = if let Some(ca_file) = &server.ca_file {
+                rumqttc::Transport::tls(fs::read(ca_file)?, None, None)
+            } else {
+                rumqttc::Transport::tls_with_default_config()
+            };
+            opts.set_transport(transport);
+        }
+
+        Ok(opts)
+    }
+
+    /// Forward publish packets to the channel, until the connection fails
+    async fn poll(mut eventloop: EventLoop, base_topic: String, tx: mpsc::Sender<RawMessage>) {
+        let prefix = format!("{base_topic}/");
+
+        loop {
+            let publish = match eventloop.poll().await {
+                Ok(Event::Incoming(Incoming::Publish(publish))) => publish,
+                Ok(_) => continue,
+                Err(err) => {
+                    log::error!("Mqtt connection failed: {err}");
+                    break;
+                }
+            };
+
+            let Some(topic) = publish.topic.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            /* we subscribe to everything, so our own requests come back to us */
+            if topic.starts_with("bridge/request/") || topic.ends_with("/set") {
+                continue;
+            }
+
+            /* most payloads are json, but some (e.g. legacy availability) are plain text */
+            let payload = serde_json::from_slice(&publish.payload).unwrap_or_else(|_| {
+                Value::String(String::from_utf8_lossy(&publish.payload).into_owned())
+            });
+
+            let msg = RawMessage {
+                topic: topic.to_string(),
+                payload,
+            };
+
+            if tx.send(msg).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    pub async fn connect(server: &Z2mServer) -> ApiResult<Self> {
+        let opts = Self::options(server)?;
+        let base_topic = server.base_topic().to_string();
+
+        let (client, eventloop) = AsyncClient::new(opts, 64);
+        client
+            .subscribe(format!("{base_topic}/#"), QoS::AtMostOnce)
+            .await?;
+
+        let (tx, rx) = mpsc::channel(64);
+        let task = tokio::spawn(Self::poll(eventloop, base_topic.clone(), tx));
+
+        Ok(Self {
+            client,
+            base_topic,
+            rx,
+            task,
+        })
+    }
+
+    pub async fn recv(&mut self) -> ApiResult<RawMessage> {
+        self.rx.recv().await.ok_or(ApiError::UnexpectedZ2mEof)
+    }
+
+    pub async fn send(&self, msg: &RawMessage) -> ApiResult<()> {
+        let topic = format!("{}/{}", self.base_topic, msg.topic);
+        let payload = serde_json::to_vec(&msg.payload)?;
+
+        Ok(self
+            .client
+            .publish(topic, QoS::AtMostOnce, false, payload)
+            .await?)
+    }
+}
+
+impl Drop for MqttTransport {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
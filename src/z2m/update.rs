@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::hue::api::On;
+use crate::hue::api::{ColorGamut, On, SceneAction};
+use crate::model::color::HS;
 use crate::model::types::XY;
 
 #[allow(clippy::pub_underscore_fields)]
@@ -42,6 +43,18 @@ pub struct DeviceUpdate {
     pub battery: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transition: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupancy: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub illuminance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub illuminance_lux: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
 
     /* all other fields */
     #[serde(skip_serializing_if = "HashMap::is_empty")]
@@ -50,6 +63,8 @@ pub struct DeviceUpdate {
 }
 
 impl DeviceUpdate {
+    const IDENTIFY_EFFECT: &'static str = "blink";
+
     #[must_use]
     pub fn new() -> Self {
         Self::default()
@@ -85,6 +100,20 @@ impl DeviceUpdate {
         }
     }
 
+    #[must_use]
+    pub fn with_effect(self, effect: Option<&str>) -> Self {
+        Self {
+            effect: effect.map(ToString::to_string),
+            ..self
+        }
+    }
+
+    /// Transition time, in seconds
+    #[must_use]
+    pub fn with_transition(self, transition: Option<f64>) -> Self {
+        Self { transition, ..self }
+    }
+
     #[must_use]
     pub fn with_color_xy(self, xy: Option<XY>) -> Self {
         Self {
@@ -92,6 +121,88 @@ impl DeviceUpdate {
             ..self
         }
     }
+
+    /// Identify a light, by briefly blinking it
+    #[must_use]
+    pub fn with_identify(self) -> Self {
+        self.with_effect(Some(Self::IDENTIFY_EFFECT))
+    }
+
+    /// This update, without an effect that is not in `effects` (the values
+    /// of the z2m "effect" expose of a light). Identify is sent through the
+    /// z2m "identify" expose if the light has one, and is never dropped.
+    #[must_use]
+    pub fn with_supported_effect(mut self, effects: &[String], identify: bool) -> Self {
+        let Some(effect) = self.effect.take() else {
+            return self;
+        };
+
+        if effects.contains(&effect) {
+            self.effect = Some(effect);
+        } else if effect == Self::IDENTIFY_EFFECT {
+            if identify {
+                self.__.insert("identify".to_string(), json!("identify"));
+            } else {
+                self.effect = Some(effect);
+            }
+        } else {
+            log::debug!("Ignoring unsupported effect {effect:?}");
+        }
+
+        self
+    }
+
+    /// A copy of this update with the color moved inside `gamut`, if it was
+    /// outside
+    #[must_use]
+    pub fn clamped_to(&self, gamut: &ColorGamut) -> Option<Self> {
+        let xy = self.color?.xy?;
+        let clamped = gamut.clamp(xy);
+
+        (clamped != xy).then(|| self.clone().with_color_xy(Some(clamped)))
+    }
+
+    /// A copy of this update with the color temperature replaced by the
+    /// matching xy color, for lights that only support colors
+    #[must_use]
+    pub fn converted_from_ct(&self) -> Option<Self> {
+        let mirek = self.color_temp?;
+
+        Some(Self {
+            color_temp: None,
+            color: self
+                .color
+                .or_else(|| Some(DeviceColor::xy(XY::from_mirek(mirek)))),
+            ..self.clone()
+        })
+    }
+
+    /// A copy of this update with the color expressed as hue/saturation,
+    /// for lights that do not support xy colors
+    #[must_use]
+    pub fn converted_to_hs(&self) -> Option<Self> {
+        let hs = self.color?.xy?.to_hs();
+
+        Some(Self {
+            color: Some(DeviceColor::hs(hs.hue, hs.sat * 100.0)),
+            ..self.clone()
+        })
+    }
+}
+
+impl From<&SceneAction> for DeviceUpdate {
+    fn from(action: &SceneAction) -> Self {
+        Self::default()
+            .with_state(action.on.map(|on| on.on))
+            .with_brightness(
+                action
+                    .dimming
+                    .as_ref()
+                    .map(|dim| dim.brightness / 100.0 * 254.0),
+            )
+            .with_color_temp(action.color_temperature.as_ref().map(|ct| ct.mirek))
+            .with_color_xy(action.color.as_ref().map(|col| col.xy))
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
@@ -133,6 +244,15 @@ impl DeviceColor {
             xy: None,
         }
     }
+
+    /// The reported color as xy, converting from hue/saturation if needed
+    #[must_use]
+    pub fn as_xy(&self) -> Option<XY> {
+        self.xy.or_else(|| {
+            let hs = HS::new(self.hue?, self.saturation? / 100.0);
+            Some(XY::from_hs(hs))
+        })
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone, Default)]
@@ -208,3 +328,46 @@ impl From<DeviceState> for On {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DeviceUpdate;
+
+    #[test]
+    fn unsupported_effect_is_dropped() {
+        let upd = DeviceUpdate::default()
+            .with_effect(Some("colorloop"))
+            .with_supported_effect(&["blink".to_string()], false);
+
+        assert_eq!(upd.effect, None);
+    }
+
+    #[test]
+    fn supported_effect_is_kept() {
+        let effects = ["blink".to_string(), "colorloop".to_string()];
+        let upd = DeviceUpdate::default()
+            .with_effect(Some("colorloop"))
+            .with_supported_effect(&effects, true);
+
+        assert_eq!(upd.effect.as_deref(), Some("colorloop"));
+    }
+
+    #[test]
+    fn identify_without_effect_expose() {
+        let upd = DeviceUpdate::default()
+            .with_identify()
+            .with_supported_effect(&[], false);
+
+        assert_eq!(upd.effect.as_deref(), Some("blink"));
+    }
+
+    #[test]
+    fn identify_through_identify_expose() {
+        let upd = DeviceUpdate::default()
+            .with_identify()
+            .with_supported_effect(&[], true);
+
+        assert_eq!(upd.effect, None);
+        assert_eq!(upd.__.get("identify"), Some(&serde_json::json!("identify")));
+    }
+}